@@ -1,6 +1,11 @@
 use crate::{
+    export::{self, Target},
     fatal,
-    utils::{Color, color},
+    import::Source,
+    party::{self, TieBreak},
+    report::Format,
+    run,
+    utils::{Color, Exit, color},
 };
 
 fn help(prog: &str) -> ! {
@@ -36,40 +41,516 @@ fn help(prog: &str) -> ! {
         input
     );
     println!(
-        "    {} {}\n\ttokenizes, parses and starts the provided file",
+        "    {} {} {}\n\ttokenizes, parses and starts the provided file",
         color(Color::Yellow, "start"),
+        input,
+        color(Color::Grey, "[options]")
+    );
+    println!(
+        "\t{}\tfixes the seed used to draw questions",
+        color(Color::Grey, "--seed <n>")
+    );
+    println!(
+        "\t{}\t\tholds back feedback until a review at the end",
+        color(Color::Grey, "--exam")
+    );
+    println!(
+        "\t{}\tsaves progress to a session file after each question",
+        color(Color::Grey, "--session <file>")
+    );
+    println!(
+        "\t{}\twrites the result as json or csv",
+        color(Color::Grey, "--report <format> --output <file>")
+    );
+    println!(
+        "\t{}\t\thides the running score shown with each question",
+        color(Color::Grey, "--hide-score")
+    );
+    println!(
+        "\t{}\t\truns full screen with arrow-key option selection, if the terminal allows",
+        color(Color::Grey, "--tui")
+    );
+    println!(
+        "\t{}\tre-asks missed questions until answered correctly n times in a row",
+        color(Color::Grey, "--practice <n>")
+    );
+    println!(
+        "\t{}\t\tdoesn't record the result in the score history",
+        color(Color::Grey, "--no-history")
+    );
+    println!(
+        "\t{}\tonly asks questions with this tag",
+        color(Color::Grey, "--tag <tag>")
+    );
+    println!(
+        "\t{}\tskips questions with this tag",
+        color(Color::Grey, "--exclude-tag <tag>")
+    );
+    println!(
+        "    {} {}\n\tcontinues a quiz saved with --session",
+        color(Color::Yellow, "resume"),
+        color(Color::Grey, "<session>")
+    );
+    println!(
+        "    {} {} {} {}\n\tgrades one answer per line, or \"<n>: <answer>\" lines, without prompting\n\treads the answers from stdin if no file is given, and accepts --seed, --tag,\n\t--exclude-tag and --report like start; --seed is required if the quiz draws",
+        color(Color::Yellow, "grade"),
+        input,
+        color(Color::Grey, "[answers.txt]"),
+        color(Color::Grey, "[options]")
+    );
+    println!(
+        "    {} {}\n\tshows past scores and the most missed questions of the provided file",
+        color(Color::Yellow, "stats"),
         input
     );
 
-    std::process::exit(0);
+    println!(
+        "    {} {} {}\n\tasks the cards that are due for spaced repetition, accepts --tag and --exclude-tag",
+        color(Color::Yellow, "review"),
+        input,
+        color(Color::Grey, "[options]")
+    );
+
+    println!(
+        "    {} {} {}\n\tshows each question as a flashcard to reveal and rate yourself, accepts --tag,\n\t--exclude-tag and --schedule, which only shows due cards and feeds the ratings into review",
+        color(Color::Yellow, "cards"),
+        input,
+        color(Color::Grey, "[options]")
+    );
+
+    println!(
+        "    {} {} {} {}\n\tplays a hot-seat game where every player answers every question",
+        color(Color::Yellow, "party"),
+        input,
+        color(Color::Grey, "--players <a,b,c>"),
+        color(Color::Grey, "[options]")
+    );
+    println!(
+        "\t{}\t\tgives each question to one player in turn instead",
+        color(Color::Grey, "--rotate")
+    );
+    println!(
+        "\t{}\tbreaks ties by time, correct or none, defaults to time",
+        color(Color::Grey, "--tie-break <rule>")
+    );
+
+    println!(
+        "    {} {} {} {}\n\thosts the quiz for line-based tcp clients such as nc or telnet",
+        color(Color::Yellow, "host"),
+        input,
+        color(Color::Grey, "--listen <address:port>"),
+        color(Color::Grey, "[options]")
+    );
+    println!(
+        "\t{}\t\tstarts once n players have joined instead of on enter",
+        color(Color::Grey, "--expect <n>")
+    );
+    println!(
+        "\t{}\tseconds to answer questions without a time limit, defaults to 30",
+        color(Color::Grey, "--deadline <secs>")
+    );
+
+    println!(
+        "    {} {} {}\n\tserves the quiz as a web page and grades submissions on the server, accepts\n\t--port (defaults to 8080), --seed, --tag, --exclude-tag and --report",
+        color(Color::Yellow, "serve"),
+        input,
+        color(Color::Grey, "[options]")
+    );
+
+    println!(
+        "    {} {} {} {}\n\twrites the quiz in another format to stdout, accepts --tag and --exclude-tag",
+        color(Color::Yellow, "export"),
+        color(Color::Grey, "<format>"),
+        input,
+        color(Color::Grey, "[options]")
+    );
+    println!(
+        "\t{}\t\ta self-contained page that runs the quiz offline, accepts --seed, --exam\n\t\t\tand --hide-score",
+        color(Color::Grey, "html")
+    );
+    println!(
+        "\t{}\t\thashes the answers in the page instead of writing them out",
+        color(Color::Grey, "--obfuscate")
+    );
+    println!(
+        "\t{}\ta printable paper with lettered options and answer lines, accepts --seed",
+        color(Color::Grey, "markdown")
+    );
+    println!(
+        "\t{}\tlists the correct answers and their marks instead",
+        color(Color::Grey, "--answer-key")
+    );
+    println!(
+        "\t{}\t\ta document for the exam class with points and choices, accepts --seed",
+        color(Color::Grey, "latex")
+    );
+    println!(
+        "\t{}\t\tprints the correct choices and answers in the document",
+        color(Color::Grey, "--solutions")
+    );
+    println!(
+        "\t{}\t\ta moodle question bank, with the quiz settings kept in // qqg comments",
+        color(Color::Grey, "gift")
+    );
+    println!(
+        "\t{}\ta moodle xml question bank with categories, grades and tags",
+        color(Color::Grey, "moodle-xml")
+    );
+    println!(
+        "\t{}\t\ta qti 2.1 content package as a zip file with one item per question",
+        color(Color::Grey, "qti")
+    );
+    println!(
+        "    {} {} {}\n\tconverts a file from another format to .qq on stdout, and reports what couldn't\n\tbe converted on stderr",
+        color(Color::Yellow, "import"),
+        color(Color::Grey, "<format>"),
+        color(Color::Grey, "<file>")
+    );
+    println!(
+        "\t{}\t\tshort answer, multiple choice, numerical, true/false and matching questions",
+        color(Color::Grey, "gift")
+    );
+
+    println!("\n{}", color(Color::Yellow, "exit codes"));
+    for (code, meaning) in [
+        (Exit::Passed, "the quiz was passed"),
+        (Exit::Failed, "the quiz was failed"),
+        (Exit::Usage, "the command line was invalid"),
+        (Exit::Quiz, "the quiz or session file was invalid"),
+        (
+            Exit::Io,
+            "a file or the terminal couldn't be read or written",
+        ),
+        (
+            Exit::Aborted,
+            "the quiz was aborted with :quit or end of input",
+        ),
+    ] {
+        println!(
+            "    {}\t{}",
+            color(Color::Yellow, &(code as i32).to_string()),
+            meaning
+        );
+    }
+
+    std::process::exit(Exit::Passed as i32);
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq)]
 pub enum Command {
     Parse,
     Token,
+    #[default]
     Start,
+    Resume,
+    Grade,
+    Stats,
+    Review,
+    Cards,
+    Party,
+    Host,
+    Serve,
+    Export(Target),
+    Import(Source),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Parse => "parse",
+            Command::Token => "token",
+            Command::Start => "start",
+            Command::Resume => "resume",
+            Command::Grade => "grade",
+            Command::Stats => "stats",
+            Command::Review => "review",
+            Command::Cards => "cards",
+            Command::Party => "party",
+            Command::Host => "host",
+            Command::Serve => "serve",
+            Command::Export(_) => "export",
+            Command::Import(_) => "import",
+        }
+    }
+}
+
+const OPTIONS: &[&str] = &[
+    "--seed",
+    "--session",
+    "--report",
+    "--output",
+    "--exam",
+    "--practice",
+    "--players",
+    "--listen",
+    "--port",
+    "--expect",
+    "--deadline",
+    "--rotate",
+    "--tie-break",
+    "--solutions",
+    "--answer-key",
+    "--obfuscate",
+    "--hide-score",
+    "--tui",
+    "--schedule",
+    "--no-history",
+    "--tag",
+    "--exclude-tag",
+];
+
+pub fn accepts(cmd: &Command, flag: &str) -> bool {
+    let flags: &[&str] = match cmd {
+        Command::Parse | Command::Token | Command::Resume | Command::Stats | Command::Import(_) => {
+            &[]
+        }
+        Command::Start => &[
+            "--seed",
+            "--exam",
+            "--session",
+            "--report",
+            "--output",
+            "--hide-score",
+            "--tui",
+            "--practice",
+            "--no-history",
+            "--tag",
+            "--exclude-tag",
+        ],
+        Command::Grade => &["--seed", "--report", "--output", "--tag", "--exclude-tag"],
+        Command::Review => &["--tag", "--exclude-tag"],
+        Command::Cards => &["--schedule", "--tag", "--exclude-tag"],
+        Command::Party => &[
+            "--seed",
+            "--players",
+            "--rotate",
+            "--tie-break",
+            "--tag",
+            "--exclude-tag",
+        ],
+        Command::Host => &[
+            "--seed",
+            "--listen",
+            "--expect",
+            "--deadline",
+            "--tie-break",
+            "--tag",
+            "--exclude-tag",
+        ],
+        Command::Serve => &[
+            "--seed",
+            "--port",
+            "--report",
+            "--output",
+            "--tag",
+            "--exclude-tag",
+        ],
+        Command::Export(Target::Html) => &[
+            "--seed",
+            "--exam",
+            "--hide-score",
+            "--obfuscate",
+            "--tag",
+            "--exclude-tag",
+        ],
+        Command::Export(Target::Markdown) => &["--seed", "--answer-key", "--tag", "--exclude-tag"],
+        Command::Export(Target::Latex) => &["--seed", "--solutions", "--tag", "--exclude-tag"],
+        Command::Export(_) => &["--tag", "--exclude-tag"],
+    };
+
+    flags.contains(&flag)
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
 pub struct Info {
     pub cmd: Command,
     pub file: String,
+    pub answers: Option<String>,
+    pub opts: run::Options,
+    pub party: party::Options,
+    pub export: export::Options,
+    pub port: Option<u16>,
 }
 
-fn get_filename(args: &mut impl Iterator<Item = String>) -> String {
-    if let Some(file) = args.next() {
-        if args.next().is_some() {
+fn get_value(args: &mut impl Iterator<Item = String>, flag: &str) -> String {
+    args.next().unwrap_or_else(|| {
+        fatal!(
+            "{}",
+            color(Color::Red, &format!("expected value after {}", flag))
+        )
+    })
+}
+
+fn get_inputs(args: &mut impl Iterator<Item = String>, info: &mut Info) {
+    let mut file = None;
+    let mut format = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--") && !OPTIONS.contains(&arg.as_str()) {
             fatal!(
                 "{}",
-                color(Color::Red, "encountered too many inputs; expected one")
+                color(
+                    Color::Red,
+                    &format!("encountered unrecognized option {}", arg)
+                )
+            );
+        } else if arg.starts_with("--") && !accepts(&info.cmd, &arg) {
+            fatal!(
+                "{}",
+                color(
+                    Color::Red,
+                    &format!(
+                        "encountered option {} that {} doesn't accept",
+                        arg,
+                        info.cmd.name()
+                    )
+                )
             );
         }
 
-        file
-    } else {
-        fatal!("{}", color(Color::Red, "expected input file"));
+        if arg == "--seed" {
+            info.opts.seed = Some(get_value(args, &arg).parse().unwrap_or_else(|e| {
+                fatal!(
+                    "{}",
+                    color(Color::Red, &format!("failed to parse seed: {}", e))
+                )
+            }));
+        } else if arg == "--session" {
+            info.opts.session = Some(get_value(args, &arg));
+        } else if arg == "--report" {
+            let name = get_value(args, &arg);
+            format = Some(Format::from_name(&name).unwrap_or_else(|| {
+                fatal!(
+                    "{}",
+                    color(
+                        Color::Red,
+                        &format!("encountered unrecognized report format {}", name)
+                    )
+                )
+            }));
+        } else if arg == "--output" {
+            output = Some(get_value(args, &arg));
+        } else if arg == "--exam" {
+            info.opts.exam = true;
+        } else if arg == "--practice" {
+            info.opts.practice = Some(
+                get_value(args, &arg)
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .unwrap_or_else(|| {
+                        fatal!(
+                            "{}",
+                            color(Color::Red, "expected a positive streak after --practice")
+                        )
+                    }),
+            );
+        } else if arg == "--players" {
+            for name in get_value(args, &arg).split(',').map(str::trim) {
+                if name.is_empty() || info.party.players.iter().any(|p| p == name) {
+                    fatal!(
+                        "{}",
+                        color(
+                            Color::Red,
+                            &format!("encountered empty or repeated player name {:?}", name)
+                        )
+                    );
+                }
+                info.party.players.push(name.to_string());
+            }
+        } else if arg == "--listen" {
+            info.party.listen = Some(get_value(args, &arg));
+        } else if arg == "--port" {
+            info.port = Some(get_value(args, &arg).parse().unwrap_or_else(|e| {
+                fatal!(
+                    "{}",
+                    color(Color::Red, &format!("failed to parse port: {}", e))
+                )
+            }));
+        } else if arg == "--expect" {
+            info.party.expect = Some(
+                get_value(args, &arg)
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .unwrap_or_else(|| {
+                        fatal!(
+                            "{}",
+                            color(
+                                Color::Red,
+                                "expected a positive player count after --expect"
+                            )
+                        )
+                    }),
+            );
+        } else if arg == "--deadline" {
+            info.party.deadline = Some(
+                get_value(args, &arg)
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .unwrap_or_else(|| {
+                        fatal!(
+                            "{}",
+                            color(
+                                Color::Red,
+                                "expected a positive number of seconds after --deadline"
+                            )
+                        )
+                    }),
+            );
+        } else if arg == "--rotate" {
+            info.party.rotate = true;
+        } else if arg == "--tie-break" {
+            let name = get_value(args, &arg);
+            info.party.tie_break = TieBreak::from_name(&name).unwrap_or_else(|| {
+                fatal!(
+                    "{}",
+                    color(
+                        Color::Red,
+                        &format!("encountered unrecognized tie-break {}", name)
+                    )
+                )
+            });
+        } else if arg == "--solutions" {
+            info.export.solutions = true;
+        } else if arg == "--answer-key" {
+            info.export.answer_key = true;
+        } else if arg == "--obfuscate" {
+            info.export.obfuscate = true;
+        } else if arg == "--hide-score" {
+            info.opts.hide_score = true;
+        } else if arg == "--tui" {
+            info.opts.tui = true;
+        } else if arg == "--schedule" {
+            info.opts.schedule = true;
+        } else if arg == "--no-history" {
+            info.opts.no_history = true;
+        } else if arg == "--tag" {
+            info.opts.tags.push(get_value(args, &arg));
+        } else if arg == "--exclude-tag" {
+            info.opts.exclude.push(get_value(args, &arg));
+        } else if file.is_none() {
+            file = Some(arg);
+        } else if info.cmd == Command::Grade && info.answers.is_none() {
+            info.answers = Some(arg);
+        } else {
+            fatal!(
+                "{}",
+                color(Color::Red, "encountered too many inputs; expected one")
+            );
+        }
     }
+
+    info.opts.report = match (format, output) {
+        (Some(format), Some(output)) => Some((format, output)),
+        (None, None) => None,
+        (Some(_), None) => fatal!("{}", color(Color::Red, "expected --output with --report")),
+        (None, Some(_)) => fatal!("{}", color(Color::Red, "expected --report with --output")),
+    };
+
+    info.file = file.unwrap_or_else(|| fatal!("{}", color(Color::Red, "expected input file")));
 }
 
 pub fn parse(mut args: impl Iterator<Item = String>) -> Info {
@@ -80,27 +561,62 @@ pub fn parse(mut args: impl Iterator<Item = String>) -> Info {
         .next()
         .unwrap_or_else(|| fatal!("{}", color(Color::Red, "expected subcommand")));
 
-    if cmd == "help" {
-        help(&prog);
-    } else if cmd == "token" {
-        Info {
-            cmd: Command::Token,
-            file: get_filename(&mut args),
-        }
-    } else if cmd == "parse" {
-        Info {
-            cmd: Command::Parse,
-            file: get_filename(&mut args),
-        }
-    } else if cmd == "start" {
-        Info {
-            cmd: Command::Start,
-            file: get_filename(&mut args),
-        }
-    } else {
-        fatal!(
-            "{}",
-            color(Color::Red, "encountered unrecognized subcommand")
-        );
-    }
+    let mut info = Info {
+        cmd: if cmd == "help" {
+            help(&prog);
+        } else if cmd == "token" {
+            Command::Token
+        } else if cmd == "parse" {
+            Command::Parse
+        } else if cmd == "start" {
+            Command::Start
+        } else if cmd == "resume" {
+            Command::Resume
+        } else if cmd == "grade" {
+            Command::Grade
+        } else if cmd == "stats" {
+            Command::Stats
+        } else if cmd == "review" {
+            Command::Review
+        } else if cmd == "cards" {
+            Command::Cards
+        } else if cmd == "party" {
+            Command::Party
+        } else if cmd == "host" {
+            Command::Host
+        } else if cmd == "serve" {
+            Command::Serve
+        } else if cmd == "import" {
+            let name = get_value(&mut args, &cmd);
+            Command::Import(Source::from_name(&name).unwrap_or_else(|| {
+                fatal!(
+                    "{}",
+                    color(
+                        Color::Red,
+                        &format!("encountered unrecognized import format {}", name)
+                    )
+                )
+            }))
+        } else if cmd == "export" {
+            let name = get_value(&mut args, &cmd);
+            Command::Export(Target::from_name(&name).unwrap_or_else(|| {
+                fatal!(
+                    "{}",
+                    color(
+                        Color::Red,
+                        &format!("encountered unrecognized export format {}", name)
+                    )
+                )
+            }))
+        } else {
+            fatal!(
+                "{}",
+                color(Color::Red, "encountered unrecognized subcommand")
+            );
+        },
+        ..Default::default()
+    };
+
+    get_inputs(&mut args, &mut info);
+    info
 }
@@ -1,17 +1,21 @@
 use crate::parse_error;
 use crate::token::{Token, TokenKind, TokenStream};
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Answer {
     pub answers: Vec<String>,
     pub options: Vec<usize>,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Question {
     pub answer: Answer,
     pub text: String,
     pub value: isize,
+    pub section: Option<usize>,
+    pub tags: Vec<String>,
+    pub time: Option<u64>,
+    pub attempts: Option<usize>,
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -21,10 +25,38 @@ pub struct Metaline {
     pub pass: isize,
 }
 
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub pass: isize,
+    pub draw: Option<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Pool {
+    pub tag: String,
+    pub draw: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Quiz,
+    Exam,
+}
+
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Quiz {
     pub metaline: Metaline,
     pub questions: Vec<Question>,
+    pub sections: Vec<Section>,
+    pub draw: Option<usize>,
+    pub pools: Vec<Pool>,
+    pub strict: bool,
+    pub time: Option<u64>,
+    pub attempts: Option<usize>,
+    pub decay: Option<isize>,
+    pub mode: Mode,
 }
 
 fn next(tokens: &mut TokenStream, file: &str, last: Token, want: TokenKind) -> Token {
@@ -89,6 +121,40 @@ fn next_number(tokens: &mut TokenStream, last: Token, file: &str) -> Token {
     }
 }
 
+fn next_duration(tokens: &mut TokenStream, last: Token, file: &str) -> u64 {
+    match tokens.pop_front() {
+        Some(token) => match token.kind {
+            TokenKind::Duration(n) if n > 0 => n,
+            TokenKind::Number(n) if n > 0 => n as u64,
+            _ => parse_error!(
+                token,
+                &format!(
+                    "encountered unexpected {:?}; expected positive Duration",
+                    token.kind
+                ),
+                file
+            ),
+        },
+        None => parse_error!(
+            last,
+            "encountered unexpected end of input; expected Duration",
+            file
+        ),
+    }
+}
+
+fn ify_attempts(tokens: &mut TokenStream, last: Token, file: &str) -> usize {
+    let count = next_number(tokens, last, file);
+    match count.kind {
+        TokenKind::Number(n) if n > 0 => n as usize,
+        _ => parse_error!(
+            count,
+            "expected positive Number in Attempts directive",
+            file
+        ),
+    }
+}
+
 fn ify_answer(tokens: &mut TokenStream, last: Token, file: &str) -> (Answer, Token) {
     let mut stuff = next(tokens, file, last, TokenKind::LBrace);
     let mut answer = Answer::default();
@@ -146,6 +212,47 @@ fn ify_answer(tokens: &mut TokenStream, last: Token, file: &str) -> (Answer, Tok
     (answer, stuff)
 }
 
+fn ify_tags(tokens: &mut TokenStream, last: Token, file: &str) -> (Vec<String>, Token) {
+    let mut stuff = next(tokens, file, last, TokenKind::LBrace);
+    let mut tags = Vec::new();
+
+    while let Some(token) = tokens.pop_front() {
+        match token.kind {
+            TokenKind::RBrace => {
+                stuff = token;
+                break;
+            }
+            TokenKind::String(ref s) => {
+                tags.push(s.to_string());
+                stuff = token;
+
+                if let Some(tok) = tokens.front()
+                    && tok.kind == TokenKind::Comma
+                {
+                    stuff = tokens.pop_front().unwrap_or_else(|| unreachable!());
+                }
+            }
+            _ => parse_error!(
+                token,
+                &format!("encountered unexpected {:?}; expected String", token.kind),
+                file
+            ),
+        }
+    }
+
+    if stuff.kind != TokenKind::RBrace {
+        parse_error!(stuff, "encountered unterminated Tags directive", file);
+    }
+
+    if let Some(token) = tokens.front()
+        && token.kind == TokenKind::Comma
+    {
+        tokens.pop_front();
+    }
+
+    (tags, stuff)
+}
+
 fn ify_question(tokens: &mut TokenStream, last: Token, file: &str) -> Question {
     let mut stuff = next_string(tokens, file, last);
     let mut question = Question::default();
@@ -181,10 +288,13 @@ fn ify_question(tokens: &mut TokenStream, last: Token, file: &str) -> Question {
                 }
             }
             TokenKind::Answer => (question.answer, stuff) = ify_answer(tokens, token, file),
+            TokenKind::Tags => (question.tags, stuff) = ify_tags(tokens, token, file),
+            TokenKind::Time => question.time = Some(next_duration(tokens, token, file)),
+            TokenKind::Attempts => question.attempts = Some(ify_attempts(tokens, token, file)),
             _ => parse_error!(
                 token,
                 &format!(
-                    "encountered unexpected {:?}; expected Answer or Value",
+                    "encountered unexpected {:?}; expected Answer, Value, Tags, Time or Attempts",
                     token.kind
                 ),
                 file
@@ -225,6 +335,63 @@ fn ify_metaline(tokens: &mut TokenStream, last: Token, file: &str) -> Metaline {
     metaline
 }
 
+fn ify_draw(tokens: &mut TokenStream, last: Token, file: &str) -> usize {
+    let count = next_number(tokens, last, file);
+    match count.kind {
+        TokenKind::Number(n) if n > 0 => n as usize,
+        _ => parse_error!(count, "expected positive Number in Draw directive", file),
+    }
+}
+
+fn ify_section(tokens: &mut TokenStream, last: Token, file: &str, quiz: &mut Quiz) {
+    let name = next_string(tokens, file, last);
+    let mut section = Section::default();
+    if let TokenKind::String(ref s) = name.kind {
+        section.name = s.to_string();
+    } else {
+        unreachable!();
+    }
+
+    let pass = next(tokens, file, name, TokenKind::Pass);
+    let passnum = next_number(tokens, pass, file);
+    match passnum.kind {
+        TokenKind::Number(n) => section.pass = n,
+        _ => unreachable!(),
+    }
+
+    let stuff = next(tokens, file, passnum, TokenKind::LBrace);
+    let mut closed = false;
+
+    while let Some(token) = tokens.pop_front() {
+        match token.kind {
+            TokenKind::RBrace => {
+                closed = true;
+                break;
+            }
+            TokenKind::Question => {
+                let mut question = ify_question(tokens, token, file);
+                question.section = Some(quiz.sections.len());
+                quiz.questions.push(question);
+            }
+            TokenKind::Draw => section.draw = Some(ify_draw(tokens, token, file)),
+            _ => parse_error!(
+                token,
+                &format!(
+                    "encountered unexpected {:?}; expected Question or Draw",
+                    token.kind
+                ),
+                file
+            ),
+        }
+    }
+
+    if !closed {
+        parse_error!(stuff, "encountered unterminated Section directive", file);
+    }
+
+    quiz.sections.push(section);
+}
+
 pub fn ify(tokens: &mut TokenStream, file: &str) -> Quiz {
     let mut quiz = Quiz::default();
 
@@ -232,10 +399,69 @@ pub fn ify(tokens: &mut TokenStream, file: &str) -> Quiz {
         match token.kind {
             TokenKind::Title => quiz.metaline = ify_metaline(tokens, token, file),
             TokenKind::Question => quiz.questions.push(ify_question(tokens, token, file)),
+            TokenKind::Draw => {
+                let draw = ify_draw(tokens, token, file);
+
+                if let Some(tok) = tokens.front()
+                    && tok.kind == TokenKind::From
+                {
+                    let from = tokens.pop_front().unwrap_or_else(|| unreachable!());
+                    let tag = next_string(tokens, file, from);
+                    let TokenKind::String(ref s) = tag.kind else {
+                        unreachable!();
+                    };
+
+                    if quiz.pools.iter().any(|p| p.tag == *s) {
+                        parse_error!(tag, "encountered duplicate Draw directive for tag", file);
+                    }
+                    quiz.pools.push(Pool {
+                        tag: s.to_string(),
+                        draw,
+                    });
+                } else {
+                    quiz.draw = Some(draw);
+                }
+            }
+            TokenKind::Section => ify_section(tokens, token, file, &mut quiz),
+            TokenKind::Strict => quiz.strict = true,
+            TokenKind::Time => quiz.time = Some(next_duration(tokens, token, file)),
+            TokenKind::Attempts => quiz.attempts = Some(ify_attempts(tokens, token, file)),
+            TokenKind::Decay => {
+                let percent = next_number(tokens, token, file);
+                match percent.kind {
+                    TokenKind::Number(n @ 0..=100) => quiz.decay = Some(n),
+                    _ => parse_error!(
+                        percent,
+                        "expected Number between 0 and 100 in Decay directive",
+                        file
+                    ),
+                }
+            }
+            TokenKind::Mode => {
+                let mode = tokens.pop_front().unwrap_or_else(|| {
+                    parse_error!(
+                        token,
+                        "encountered unexpected end of input; expected Quiz or Exam",
+                        file
+                    )
+                });
+                match mode.kind {
+                    TokenKind::Quiz => quiz.mode = Mode::Quiz,
+                    TokenKind::Exam => quiz.mode = Mode::Exam,
+                    _ => parse_error!(
+                        mode,
+                        &format!(
+                            "encountered unexpected {:?}; expected Quiz or Exam",
+                            mode.kind
+                        ),
+                        file
+                    ),
+                }
+            }
             _ => parse_error!(
                 token,
                 &format!(
-                    "encountered unexpected top-level directive {:?}; expected Title or Question",
+                    "encountered unexpected top-level directive {:?}; expected Title, Question, Section, Draw, Strict, Time, Attempts, Decay or Mode",
                     token.kind
                 ),
                 file
@@ -0,0 +1,162 @@
+use crate::{
+    ast::Quiz,
+    review,
+    run::{self, Input, Options},
+    utils::{Color, Exit, color},
+};
+use std::collections::VecDeque;
+use std::sync::mpsc::Receiver;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Rating {
+    pub const ALL: [Rating; 4] = [Rating::Again, Rating::Hard, Rating::Good, Rating::Easy];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rating::Again => "again",
+            Rating::Hard => "hard",
+            Rating::Good => "good",
+            Rating::Easy => "easy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .enumerate()
+            .find(|(n, r)| name == r.name() || name == (n + 1).to_string())
+            .map(|(_, r)| r)
+    }
+
+    pub fn quality(&self) -> u32 {
+        match self {
+            Rating::Again => 1,
+            Rating::Hard => 3,
+            Rating::Good => 4,
+            Rating::Easy => 5,
+        }
+    }
+}
+
+fn rate(lines: &Receiver<String>) -> Option<Rating> {
+    loop {
+        println!(
+            "{}",
+            color(Color::Grey, "rate it: 1 again, 2 hard, 3 good, 4 easy")
+        );
+
+        match run::prompt(lines, None) {
+            Input::Line(line) if line.trim() == ":quit" => return None,
+            Input::Line(line) => match Rating::from_name(line.trim()) {
+                Some(rating) => return Some(rating),
+                None => println!("{}", color(Color::Red, "unrecognized rating")),
+            },
+            _ => return None,
+        }
+    }
+}
+
+pub fn cards(quiz: &Quiz, file: &str, opts: &Options) -> Exit {
+    let today = review::today();
+    let mut cards = Vec::new();
+    let mut deck = Vec::new();
+
+    let mut queue = if opts.schedule {
+        cards = review::load();
+        deck = review::deck(quiz, review::key(file), opts, &mut cards, today).0;
+        review::due(&cards, &deck, today)
+            .into_iter()
+            .map(|(index, pos)| (index, Some(pos)))
+            .collect::<VecDeque<_>>()
+    } else {
+        (0..quiz.questions.len())
+            .filter(|&index| opts.keeps(&quiz.questions[index]))
+            .map(|index| (index, None))
+            .collect()
+    };
+
+    println!(
+        "{}\n{} {}\n",
+        color(Color::Yellow, &quiz.metaline.title),
+        color(Color::Grey, "by"),
+        color(Color::Yellow, &quiz.metaline.by)
+    );
+    println!(
+        "{}\n",
+        color(
+            Color::Grey,
+            &format!(
+                "{} cards, press enter to reveal the answer or type :quit to stop",
+                queue.len()
+            )
+        )
+    );
+
+    let lines = run::lines();
+    let mut counts = [0; 4];
+    let mut stopped = false;
+
+    while let Some((index, pos)) = queue.pop_front() {
+        let question = &quiz.questions[index];
+        run::show(question);
+
+        let rating = match run::prompt(&lines, None) {
+            Input::Line(line) if line.trim() != ":quit" => {
+                println!(
+                    "{} {}",
+                    color(Color::Grey, "└─"),
+                    color(Color::Yellow, &run::expected(question))
+                );
+                rate(&lines)
+            }
+            _ => None,
+        };
+
+        let Some(rating) = rating else {
+            stopped = true;
+            break;
+        };
+        println!();
+
+        counts[rating as usize] += 1;
+
+        if let Some(pos) = pos {
+            cards[pos].schedule(rating.quality(), today);
+            review::save(&cards);
+        }
+
+        if rating == Rating::Again {
+            queue.push_back((index, None));
+        }
+    }
+
+    if stopped {
+        println!("\n{}", color(Color::Red, "cards stopped"));
+    }
+
+    println!(
+        "{}",
+        Rating::ALL
+            .iter()
+            .map(|r| format!(
+                "{} {}",
+                color(Color::Grey, r.name()),
+                color(Color::SuperCyan, &counts[*r as usize].to_string())
+            ))
+            .collect::<Vec<_>>()
+            .join("  ")
+    );
+
+    if opts.schedule {
+        review::upcoming(&cards, &deck);
+    }
+
+    if stopped { Exit::Aborted } else { Exit::Passed }
+}
@@ -0,0 +1,51 @@
+use crate::{ast::Quiz, run};
+
+pub mod gift;
+pub mod html;
+pub mod latex;
+pub mod markdown;
+pub mod moodle;
+pub mod qq;
+pub mod qti;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Html,
+    Markdown,
+    Latex,
+    Gift,
+    MoodleXml,
+    Qti,
+}
+
+impl Target {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "html" => Some(Target::Html),
+            "markdown" => Some(Target::Markdown),
+            "latex" => Some(Target::Latex),
+            "gift" => Some(Target::Gift),
+            "moodle-xml" => Some(Target::MoodleXml),
+            "qti" => Some(Target::Qti),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub obfuscate: bool,
+    pub answer_key: bool,
+    pub solutions: bool,
+}
+
+pub fn export(quiz: &Quiz, opts: &run::Options, setup: &Options, target: Target) -> Vec<u8> {
+    match target {
+        Target::Html => html::html(quiz, opts, setup).into_bytes(),
+        Target::Markdown => markdown::markdown(quiz, opts, setup).into_bytes(),
+        Target::Latex => latex::latex(quiz, opts, setup).into_bytes(),
+        Target::Gift => gift::gift(quiz, opts).into_bytes(),
+        Target::MoodleXml => moodle::moodle(quiz, opts).into_bytes(),
+        Target::Qti => qti::qti(quiz, opts),
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    ast::{Mode, Question, Quiz},
+    export::qq::time,
+    run::Options,
+};
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for chr in text.chars() {
+        match chr {
+            '~' | '=' | '#' | '{' | '}' | ':' | '\\' => {
+                out.push('\\');
+                out.push(chr);
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn strings(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|s| quoted(s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn weight(correct: usize) -> String {
+    match 100 % correct {
+        0 => (100 / correct).to_string(),
+        _ => format!("{:.5}", 100.0 / correct as f64),
+    }
+}
+
+fn question(question: &Question) -> String {
+    let mut meta = format!("// qqg value {}", question.value);
+    if !question.tags.is_empty() {
+        meta += &format!(" tags {}", strings(&question.tags));
+    }
+    if let Some(secs) = question.time {
+        meta += &format!(" time {}", time(secs));
+    }
+    if let Some(attempts) = question.attempts {
+        meta += &format!(" attempts {}", attempts);
+    }
+
+    let answer = &question.answer;
+    let correct = (1..=answer.answers.len())
+        .filter(|n| answer.options.contains(n))
+        .count();
+    let answers = answer
+        .answers
+        .iter()
+        .enumerate()
+        .map(|(index, text)| {
+            let text = escape(text);
+            match (answer.options.contains(&(index + 1)), correct) {
+                _ if answer.options.is_empty() => format!("={}", text),
+                (true, 1) => format!("={}", text),
+                (false, 1) => format!("~{}", text),
+                (true, n) => format!("~%{}%{}", weight(n), text),
+                (false, _) => format!("~%-100%{}", text),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("{}\n{} {{{}}}\n", meta, escape(&question.text), answers)
+}
+
+pub fn gift(quiz: &Quiz, opts: &Options) -> String {
+    let mut out = format!(
+        "// qqg title {} by {} pass {}\n",
+        quoted(&quiz.metaline.title),
+        quoted(&quiz.metaline.by),
+        quiz.metaline.pass
+    );
+
+    if let Some(draw) = quiz.draw {
+        out += &format!("// qqg draw {}\n", draw);
+    }
+    for pool in &quiz.pools {
+        out += &format!("// qqg draw {} from {}\n", pool.draw, quoted(&pool.tag));
+    }
+    if quiz.strict {
+        out += "// qqg strict\n";
+    }
+    if let Some(secs) = quiz.time {
+        out += &format!("// qqg time {}\n", time(secs));
+    }
+    if let Some(attempts) = quiz.attempts {
+        out += &format!("// qqg attempts {}\n", attempts);
+    }
+    if let Some(decay) = quiz.decay {
+        out += &format!("// qqg decay {}\n", decay);
+    }
+    if quiz.mode == Mode::Exam {
+        out += "// qqg mode exam\n";
+    }
+
+    let mut current = None;
+    for q in quiz.questions.iter().filter(|q| opts.keeps(q)) {
+        if q.section != current {
+            current = q.section;
+            out += &match current {
+                Some(s) => {
+                    let section = &quiz.sections[s];
+                    let mut header = format!(
+                        "\n$CATEGORY: {}\n// qqg section pass {}",
+                        section.name, section.pass
+                    );
+                    if let Some(draw) = section.draw {
+                        header += &format!(" draw {}", draw);
+                    }
+                    header + "\n"
+                }
+                None => "\n// qqg end section\n".to_string(),
+            };
+        }
+
+        out += "\n";
+        out += &question(q);
+    }
+
+    out
+}
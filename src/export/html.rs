@@ -0,0 +1,188 @@
+use crate::{
+    ast::{Mode, Question, Quiz},
+    export, html,
+    json::Json,
+    run::{self, Options},
+    utils::Rng,
+};
+
+const SCRIPT: &str = include_str!("quiz.js");
+
+pub fn digest(salt: &str, text: &str) -> String {
+    let hash = salt
+        .bytes()
+        .chain(text.bytes())
+        .fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+
+    format!("{:08x}", hash)
+}
+
+fn question(quiz: &Quiz, question: &Question, salt: Option<&str>) -> Json {
+    let hide = |text: String| match salt {
+        Some(salt) => Json::String(digest(salt, &text)),
+        None => Json::String(text),
+    };
+
+    let mut fields = vec![
+        ("text".to_string(), question.text.as_str().into()),
+        ("value".to_string(), question.value.into()),
+    ];
+
+    if let Some(section) = question.section {
+        fields.push(("section".to_string(), section.into()));
+    }
+    if let Some(pool) = run::pool(quiz, question) {
+        fields.push(("pool".to_string(), pool.into()));
+    }
+    if let Some(time) = question.time {
+        fields.push(("time".to_string(), time.into()));
+    }
+    if let Some(attempts) = question.attempts {
+        fields.push(("attempts".to_string(), attempts.into()));
+    }
+
+    if question.answer.options.is_empty() {
+        fields.push((
+            "answers".to_string(),
+            Json::Array(question.answer.answers.iter().cloned().map(hide).collect()),
+        ));
+    } else {
+        let mut set = question.answer.options.clone();
+        set.sort_unstable();
+        set.dedup();
+
+        fields.push((
+            "choices".to_string(),
+            Json::Array(
+                question
+                    .answer
+                    .answers
+                    .iter()
+                    .map(|a| a.as_str().into())
+                    .collect(),
+            ),
+        ));
+        fields.push((
+            "answers".to_string(),
+            Json::Array(
+                question
+                    .answer
+                    .options
+                    .iter()
+                    .map(|n| hide(n.to_string()))
+                    .collect(),
+            ),
+        ));
+        fields.push((
+            "set".to_string(),
+            hide(
+                set.iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        ));
+    }
+
+    if salt.is_none() {
+        fields.push((
+            "expected".to_string(),
+            run::expected(question).as_str().into(),
+        ));
+    }
+
+    Json::Object(fields)
+}
+
+pub fn html(quiz: &Quiz, opts: &Options, setup: &export::Options) -> String {
+    let salt = setup.obfuscate.then(|| {
+        format!(
+            "{:016x}",
+            Rng::new(opts.seed.unwrap_or_else(Rng::seed)).next()
+        )
+    });
+    let everything = 0..quiz.questions.len();
+
+    let mut fields = vec![
+        ("title".to_string(), quiz.metaline.title.as_str().into()),
+        ("by".to_string(), quiz.metaline.by.as_str().into()),
+        ("pass".to_string(), quiz.metaline.pass.into()),
+        (
+            "total".to_string(),
+            run::marks(quiz, everything.clone(), None).into(),
+        ),
+        ("strict".to_string(), quiz.strict.into()),
+        (
+            "exam".to_string(),
+            (opts.exam || quiz.mode == Mode::Exam).into(),
+        ),
+        ("hide_score".to_string(), opts.hide_score.into()),
+        (
+            "sections".to_string(),
+            Json::Array(
+                quiz.sections
+                    .iter()
+                    .enumerate()
+                    .map(|(s, section)| {
+                        let mut fields = vec![
+                            ("name".to_string(), section.name.as_str().into()),
+                            ("pass".to_string(), section.pass.into()),
+                            (
+                                "total".to_string(),
+                                run::marks(quiz, everything.clone(), Some(s)).into(),
+                            ),
+                        ];
+                        if let Some(draw) = section.draw {
+                            fields.push(("draw".to_string(), draw.into()));
+                        }
+                        Json::Object(fields)
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "pools".to_string(),
+            Json::Array(quiz.pools.iter().map(|p| p.draw.into()).collect()),
+        ),
+        (
+            "questions".to_string(),
+            Json::Array(
+                quiz.questions
+                    .iter()
+                    .filter(|q| opts.keeps(q))
+                    .map(|q| question(quiz, q, salt.as_deref()))
+                    .collect(),
+            ),
+        ),
+    ];
+
+    for (key, value) in [
+        ("draw", quiz.draw),
+        ("attempts", quiz.attempts),
+        ("time", quiz.time.map(|t| t as usize)),
+    ] {
+        if let Some(value) = value {
+            fields.push((key.to_string(), value.into()));
+        }
+    }
+    if let Some(decay) = quiz.decay {
+        fields.push(("decay".to_string(), decay.into()));
+    }
+    if let Some(seed) = opts.seed {
+        fields.push(("seed".to_string(), seed.to_string().as_str().into()));
+    }
+    if let Some(salt) = &salt {
+        fields.push(("salt".to_string(), salt.as_str().into()));
+    }
+
+    html::page(
+        &quiz.metaline.title,
+        &format!(
+            "<main id=\"quiz\"><noscript>this quiz needs javascript to run</noscript></main>\n<script type=\"application/json\" id=\"data\">{}</script>\n<script>\n{}</script>\n",
+            Json::Object(fields),
+            SCRIPT
+        ),
+    )
+}
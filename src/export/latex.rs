@@ -0,0 +1,112 @@
+use crate::{ast::Quiz, export, run::Options, session::Session, utils::duration};
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for chr in text.chars() {
+        match chr {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(chr);
+            }
+            '[' => out.push_str("{[}"),
+            ']' => out.push_str("{]}"),
+            '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+pub fn latex(quiz: &Quiz, opts: &Options, setup: &export::Options) -> String {
+    let session = Session::new(quiz, opts);
+
+    let mut out = String::from(
+        "\\documentclass[addpoints]{exam}\n\\usepackage[utf8]{inputenc}\n\\usepackage[T1]{fontenc}\n\n",
+    );
+    if setup.solutions {
+        out += "\\printanswers\n";
+    }
+    out += &format!(
+        "\\title{{{}}}\n\\author{{{}}}\n\\date{{}}\n\n\\begin{{document}}\n\\maketitle\n\n\\begin{{center}}\n",
+        escape(&quiz.metaline.title),
+        escape(&quiz.metaline.by)
+    );
+
+    let mut meta = vec![format!(
+        "passing marks {} of \\numpoints\\ points",
+        session.pass
+    )];
+    if let Some(time) = quiz.time {
+        meta.push(format!("time limit {}", duration(time)));
+    }
+    if quiz.strict && !quiz.sections.is_empty() {
+        meta.push("every section must be passed".to_string());
+    }
+    if session.records.len() < quiz.questions.len() {
+        meta.push(format!("seed {}", session.seed));
+    }
+    out += &meta.join(" \\textperiodcentered\\ ");
+    out += "\n\\end{center}\n\n\\begin{questions}\n";
+
+    let mut section = None;
+    for record in &session.records {
+        let question = &quiz.questions[record.index];
+
+        if question.section != section {
+            section = question.section;
+            if let Some(s) = section {
+                out += &format!(
+                    "\n\\fullwidth{{\\textbf{{{}}} (passing marks {})}}\n",
+                    escape(&quiz.sections[s].name),
+                    session.section_pass[s]
+                );
+            }
+        }
+
+        out += &format!(
+            "\n\\question[{}] {}\n",
+            question.value,
+            escape(&question.text)
+        );
+
+        if question.answer.options.is_empty() {
+            out += &format!(
+                "\\begin{{solutionorlines}}[1in]\n{}\n\\end{{solutionorlines}}\n",
+                question
+                    .answer
+                    .answers
+                    .iter()
+                    .map(|a| escape(a))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            );
+        } else {
+            let kind = match question.answer.options.len() {
+                1 => "choices",
+                _ => "checkboxes",
+            };
+
+            out += &format!("\\begin{{{}}}\n", kind);
+            for (index, answer) in question.answer.answers.iter().enumerate() {
+                out += &format!(
+                    "\\{} {}\n",
+                    if question.answer.options.contains(&(index + 1)) {
+                        "CorrectChoice"
+                    } else {
+                        "choice"
+                    },
+                    escape(answer)
+                );
+            }
+            out += &format!("\\end{{{}}}\n", kind);
+        }
+    }
+
+    out += "\\end{questions}\n\\end{document}\n";
+    out
+}
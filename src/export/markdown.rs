@@ -0,0 +1,140 @@
+use crate::{ast::Quiz, export, run::Options, session::Session, utils::duration};
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for chr in text.chars() {
+        match chr {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' => {
+                out.push('\\');
+                out.push(chr);
+            }
+            '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+pub fn letter(n: usize) -> String {
+    let mut n = n + 1;
+    let mut out = Vec::new();
+
+    while n > 0 {
+        n -= 1;
+        out.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+
+    out.iter().rev().map(|&b| b as char).collect()
+}
+
+fn marks(value: isize) -> String {
+    match value {
+        1 => "1 mark".to_string(),
+        n => format!("{} marks", n),
+    }
+}
+
+pub fn markdown(quiz: &Quiz, opts: &Options, setup: &export::Options) -> String {
+    let session = Session::new(quiz, opts);
+    let total = session
+        .records
+        .iter()
+        .map(|r| quiz.questions[r.index].value)
+        .sum::<isize>();
+
+    let mut out = format!(
+        "# {}\n\nby {}\n\n",
+        escape(&quiz.metaline.title),
+        escape(&quiz.metaline.by)
+    );
+    let mut meta = vec![
+        format!("passing marks {} of {}", session.pass, total),
+        match session.records.len() {
+            1 => "1 question".to_string(),
+            n => format!("{} questions", n),
+        },
+    ];
+    if let Some(time) = quiz.time {
+        meta.push(format!("time limit {}", duration(time)));
+    }
+    if quiz.strict && !quiz.sections.is_empty() {
+        meta.push("every section must be passed".to_string());
+    }
+    if session.records.len() < quiz.questions.len() {
+        meta.push(format!("seed {}", session.seed));
+    }
+    out += &meta.join(" · ");
+    out += "\n\n";
+
+    if setup.answer_key {
+        out += "## answer key\n\n| # | answer | marks |\n|---|--------|-------|\n";
+
+        for (n, record) in session.records.iter().enumerate() {
+            let question = &quiz.questions[record.index];
+            let answer = if question.answer.options.is_empty() {
+                question
+                    .answer
+                    .answers
+                    .iter()
+                    .map(|a| escape(a))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            } else {
+                question
+                    .answer
+                    .options
+                    .iter()
+                    .filter_map(|&o| {
+                        Some(format!(
+                            "{}) {}",
+                            letter(o - 1),
+                            escape(question.answer.answers.get(o - 1)?)
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            out += &format!("| {} | {} | {} |\n", n + 1, answer, question.value);
+        }
+
+        return out;
+    }
+
+    let mut section = None;
+    for (n, record) in session.records.iter().enumerate() {
+        let question = &quiz.questions[record.index];
+
+        if question.section != section {
+            section = question.section;
+            if let Some(s) = section {
+                out += &format!(
+                    "## {}\n\npassing marks {}\n\n",
+                    escape(&quiz.sections[s].name),
+                    session.section_pass[s]
+                );
+            }
+        }
+
+        out += &format!(
+            "**{}.** {} *({})*\n\n",
+            n + 1,
+            escape(&question.text),
+            marks(question.value)
+        );
+
+        if !question.answer.options.is_empty() {
+            for (index, answer) in question.answer.answers.iter().enumerate() {
+                out += &format!("- {}) {}\n", letter(index), escape(answer));
+            }
+            out += "\n";
+        }
+
+        out += "answer: ______________________________\n\n";
+    }
+
+    out
+}
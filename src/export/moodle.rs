@@ -0,0 +1,106 @@
+use crate::{
+    ast::{Question, Quiz},
+    export::gift::weight,
+    run::Options,
+    xml::Xml,
+};
+
+fn text(tag: &str, text: &str) -> Xml {
+    Xml::new(tag).child(Xml::new("text").text(text))
+}
+
+fn name(text: &str) -> String {
+    let mut name = text.chars().take(60).collect::<String>();
+    if text.chars().count() > 60 {
+        name.pop();
+        name.push('…');
+    }
+    name
+}
+
+fn category(path: &str) -> Xml {
+    Xml::new("question")
+        .attr("type", "category")
+        .child(text("category", &format!("$course$/top/{}", path)))
+}
+
+fn question(question: &Question) -> Xml {
+    let answer = &question.answer;
+    let correct = (1..=answer.answers.len())
+        .filter(|n| answer.options.contains(n))
+        .count();
+    // moodle only takes fractions from a fixed list, so credit that can't be split
+    // evenly goes to whichever correct option is picked, which qqg accepts as well
+    let split = matches!(correct, 2..=10 | 20);
+
+    let mut out = Xml::new("question")
+        .attr(
+            "type",
+            if answer.options.is_empty() {
+                "shortanswer"
+            } else {
+                "multichoice"
+            },
+        )
+        .child(text("name", &name(&question.text)))
+        .child(text("questiontext", &question.text).attr("format", "plain_text"))
+        .child(Xml::new("defaultgrade").text(question.value));
+
+    if answer.options.is_empty() {
+        out = out.child(Xml::new("usecase").text(1));
+    } else {
+        out = out.children([
+            Xml::new("single").text(!split),
+            Xml::new("shuffleanswers").text(0),
+            Xml::new("answernumbering").text("123"),
+        ]);
+    }
+
+    for (index, text) in answer.answers.iter().enumerate() {
+        let fraction = match (answer.options.contains(&(index + 1)), correct) {
+            _ if answer.options.is_empty() => "100".to_string(),
+            (true, n) if split => weight(n),
+            (true, _) => "100".to_string(),
+            (false, _) if split => "-100".to_string(),
+            (false, _) => "0".to_string(),
+        };
+        out = out.child(
+            Xml::new("answer")
+                .attr("fraction", fraction)
+                .attr("format", "plain_text")
+                .child(Xml::new("text").text(text)),
+        );
+    }
+
+    if !question.tags.is_empty() {
+        out = out.child(
+            Xml::new("tags").children(
+                question
+                    .tags
+                    .iter()
+                    .map(|tag| Xml::new("tag").child(Xml::new("text").text(tag))),
+            ),
+        );
+    }
+
+    out
+}
+
+pub fn moodle(quiz: &Quiz, opts: &Options) -> String {
+    let title = quiz.metaline.title.replace('/', "//");
+    let mut out = Xml::new("quiz").child(category(&title));
+    let mut current = None;
+
+    for q in quiz.questions.iter().filter(|q| opts.keeps(q)) {
+        if q.section != current {
+            current = q.section;
+            out = out.child(category(&match current {
+                Some(s) => format!("{}/{}", title, quiz.sections[s].name.replace('/', "//")),
+                None => title.clone(),
+            }));
+        }
+        out = out.child(question(q));
+    }
+
+    out.to_string()
+}
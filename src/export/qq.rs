@@ -0,0 +1,119 @@
+use crate::ast::{Mode, Question, Quiz};
+
+pub fn time(secs: u64) -> String {
+    match secs {
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+fn strings(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|s| format!("\"{}\"", s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn question(out: &mut String, question: &Question, indent: &str) {
+    let answers = question
+        .answer
+        .answers
+        .iter()
+        .enumerate()
+        .map(|(index, answer)| {
+            if question.answer.options.contains(&(index + 1)) {
+                format!("\"{}\" pass", answer)
+            } else {
+                format!("\"{}\"", answer)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    *out += &format!(
+        "{indent}question \"{}\" {{\n{indent}\tanswer {{ {} }}\n{indent}\tvalue {}\n",
+        question.text, answers, question.value
+    );
+    if !question.tags.is_empty() {
+        *out += &format!("{indent}\ttags {{ {} }}\n", strings(&question.tags));
+    }
+    if let Some(secs) = question.time {
+        *out += &format!("{indent}\ttime {}\n", time(secs));
+    }
+    if let Some(attempts) = question.attempts {
+        *out += &format!("{indent}\tattempts {}\n", attempts);
+    }
+    *out += &format!("{indent}}}\n");
+}
+
+pub fn qq(quiz: &Quiz) -> String {
+    let mut out = format!(
+        "title \"{}\" by \"{}\" pass {}\n",
+        quiz.metaline.title, quiz.metaline.by, quiz.metaline.pass
+    );
+
+    if let Some(draw) = quiz.draw {
+        out += &format!("draw {}\n", draw);
+    }
+    for pool in &quiz.pools {
+        out += &format!("draw {} from \"{}\"\n", pool.draw, pool.tag);
+    }
+    if quiz.strict {
+        out += "strict\n";
+    }
+    if let Some(secs) = quiz.time {
+        out += &format!("time {}\n", time(secs));
+    }
+    if let Some(attempts) = quiz.attempts {
+        out += &format!("attempts {}\n", attempts);
+    }
+    if let Some(decay) = quiz.decay {
+        out += &format!("decay {}\n", decay);
+    }
+    if quiz.mode == Mode::Exam {
+        out += "mode exam\n";
+    }
+
+    let open = |out: &mut String, s: usize| {
+        let section = &quiz.sections[s];
+        *out += &format!("\nsection \"{}\" pass {} {{\n", section.name, section.pass);
+        if let Some(draw) = section.draw {
+            *out += &format!("\tdraw {}\n", draw);
+        }
+    };
+    let mut next = 0;
+    let mut current = None;
+
+    for q in &quiz.questions {
+        if q.section != current {
+            if current.is_some() {
+                out += "}\n";
+            }
+            current = q.section;
+
+            if let Some(s) = current {
+                for empty in next..s {
+                    open(&mut out, empty);
+                    out += "}\n";
+                }
+                open(&mut out, s);
+                next = s + 1;
+            }
+        }
+
+        out += "\n";
+        question(&mut out, q, if current.is_some() { "\t" } else { "" });
+    }
+
+    if current.is_some() {
+        out += "}\n";
+    }
+    for empty in next..quiz.sections.len() {
+        open(&mut out, empty);
+        out += "}\n";
+    }
+
+    out
+}
@@ -0,0 +1,323 @@
+use crate::{
+    ast::{Mode, Question, Quiz},
+    run::{self, Options},
+    xml::Xml,
+    zip,
+};
+
+const QTI: &str = "http://www.imsglobal.org/xsd/imsqti_v2p1";
+const SCHEMA: &str = "http://www.imsglobal.org/xsd/imsqti_v2p1 http://www.imsglobal.org/xsd/qti/qtiv2p1/imsqti_v2p1.xsd";
+
+fn root(name: &str, id: &str, title: &str) -> Xml {
+    Xml::new(name)
+        .attr("xmlns", QTI)
+        .attr("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance")
+        .attr("xsi:schemaLocation", SCHEMA)
+        .attr("identifier", id)
+        .attr("title", title)
+}
+
+fn outcome(id: &str, kind: &str, value: impl ToString) -> Xml {
+    Xml::new("outcomeDeclaration")
+        .attr("identifier", id)
+        .attr("cardinality", "single")
+        .attr("baseType", kind)
+        .child(Xml::new("defaultValue").child(Xml::new("value").text(value)))
+}
+
+fn base(kind: &str, value: impl ToString) -> Xml {
+    Xml::new("baseValue").attr("baseType", kind).text(value)
+}
+
+fn response() -> Xml {
+    Xml::new("variable").attr("identifier", "RESPONSE")
+}
+
+fn item(question: &Question, id: &str) -> Xml {
+    let answer = &question.answer;
+    let choice = |n: usize| format!("C{}", n);
+
+    let (declaration, interaction, condition) = if answer.options.is_empty() {
+        (
+            Xml::new("responseDeclaration")
+                .attr("identifier", "RESPONSE")
+                .attr("cardinality", "single")
+                .attr("baseType", "string")
+                .child(
+                    Xml::new("correctResponse").child(Xml::new("value").text(&answer.answers[0])),
+                ),
+            Xml::new("p").child(
+                Xml::new("textEntryInteraction")
+                    .attr("responseIdentifier", "RESPONSE")
+                    .attr("expectedLength", 30),
+            ),
+            Xml::new("or").children(answer.answers.iter().map(|a| {
+                Xml::new("stringMatch")
+                    .attr("caseSensitive", true)
+                    .child(response())
+                    .child(base("string", a))
+            })),
+        )
+    } else {
+        let mut correct = answer.options.clone();
+        correct.sort_unstable();
+        correct.dedup();
+
+        let single = correct.iter().map(|&n| {
+            Xml::new("and")
+                .child(
+                    Xml::new("equal")
+                        .attr("toleranceMode", "exact")
+                        .child(Xml::new("containerSize").child(response()))
+                        .child(base("integer", 1)),
+                )
+                .child(
+                    Xml::new("member")
+                        .child(base("identifier", choice(n)))
+                        .child(response()),
+                )
+        });
+
+        (
+            Xml::new("responseDeclaration")
+                .attr("identifier", "RESPONSE")
+                .attr("cardinality", "multiple")
+                .attr("baseType", "identifier")
+                .child(
+                    Xml::new("correctResponse")
+                        .children(correct.iter().map(|&n| Xml::new("value").text(choice(n)))),
+                ),
+            Xml::new("choiceInteraction")
+                .attr("responseIdentifier", "RESPONSE")
+                .attr("shuffle", false)
+                .attr("maxChoices", if correct.len() == 1 { 1 } else { 0 })
+                .children(answer.answers.iter().enumerate().map(|(index, a)| {
+                    Xml::new("simpleChoice")
+                        .attr("identifier", choice(index + 1))
+                        .text(a)
+                })),
+            Xml::new("or")
+                .child(
+                    Xml::new("match")
+                        .child(response())
+                        .child(Xml::new("correct").attr("identifier", "RESPONSE")),
+                )
+                .children(single),
+        )
+    };
+
+    let score = |value: isize| {
+        Xml::new("setOutcomeValue")
+            .attr("identifier", "SCORE")
+            .child(base("float", value))
+    };
+
+    root("assessmentItem", id, &question.text)
+        .attr("adaptive", false)
+        .attr("timeDependent", question.time.is_some())
+        .child(declaration)
+        .child(outcome("SCORE", "float", 0))
+        .child(outcome("MAXSCORE", "float", question.value))
+        .child(
+            Xml::new("itemBody")
+                .child(Xml::new("p").text(&question.text))
+                .child(interaction),
+        )
+        .child(
+            Xml::new("responseProcessing").child(
+                Xml::new("responseCondition")
+                    .child(
+                        Xml::new("responseIf")
+                            .child(condition)
+                            .child(score(question.value)),
+                    )
+                    .child(Xml::new("responseElse").child(score(0))),
+            ),
+        )
+}
+
+fn reference(id: &str, question: &Question) -> Xml {
+    let mut out = Xml::new("assessmentItemRef")
+        .attr("identifier", id)
+        .attr("href", format!("items/{}.xml", id));
+    if let Some(time) = question.time {
+        out = out.child(Xml::new("timeLimits").attr("maxTime", time));
+    }
+    out
+}
+
+fn section(id: &str, title: &str, draw: Option<usize>) -> Xml {
+    let out = Xml::new("assessmentSection")
+        .attr("identifier", id)
+        .attr("title", title)
+        .attr("visible", true);
+    match draw {
+        Some(n) => out.child(Xml::new("selection").attr("select", n)),
+        None => out,
+    }
+}
+
+fn test(quiz: &Quiz, items: &[(String, &Question)]) -> Xml {
+    let pooled = |q: &Question| {
+        q.section.is_none_or(|s| quiz.sections[s].draw.is_none()) && run::pool(quiz, q).is_none()
+    };
+    let mut body = section("quiz", &quiz.metaline.title, None);
+
+    if let Some(draw) = quiz.draw {
+        body = body.child(
+            section("pool", &quiz.metaline.title, Some(draw)).children(
+                items
+                    .iter()
+                    .filter(|(_, q)| pooled(q))
+                    .map(|(id, q)| reference(id, q)),
+            ),
+        );
+    }
+
+    for (index, pool) in quiz.pools.iter().enumerate() {
+        body = body.child(
+            section(&format!("tag-{}", index + 1), &pool.tag, Some(pool.draw)).children(
+                items
+                    .iter()
+                    .filter(|(_, q)| run::pool(quiz, q) == Some(index))
+                    .map(|(id, q)| reference(id, q)),
+            ),
+        );
+    }
+
+    let mut parts: Vec<(Option<usize>, Xml)> = Vec::new();
+    for (id, q) in items {
+        if (quiz.draw.is_some() && pooled(q)) || run::pool(quiz, q).is_some() {
+            continue;
+        }
+        match q.section {
+            Some(s) if parts.last().is_some_and(|(p, _)| *p == Some(s)) => {
+                if let Some((_, part)) = parts.pop() {
+                    parts.push((Some(s), part.child(reference(id, q))));
+                }
+            }
+            Some(s) => {
+                let name = &quiz.sections[s].name;
+                parts.push((
+                    Some(s),
+                    section(&format!("section-{}", s + 1), name, quiz.sections[s].draw)
+                        .child(reference(id, q)),
+                ));
+            }
+            None => parts.push((None, reference(id, q))),
+        }
+    }
+    body = body.children(parts.into_iter().map(|(_, part)| part));
+
+    let total = quiz.questions.iter().map(|q| q.value).sum::<isize>();
+    let sum = |name: &str| {
+        Xml::new("sum").child(Xml::new("testVariables").attr("variableIdentifier", name))
+    };
+
+    let mut part = Xml::new("testPart")
+        .attr("identifier", "part")
+        .attr(
+            "navigationMode",
+            if quiz.mode == Mode::Exam {
+                "nonlinear"
+            } else {
+                "linear"
+            },
+        )
+        .attr("submissionMode", "individual");
+    if let Some(time) = quiz.time {
+        part = part.child(Xml::new("timeLimits").attr("maxTime", time));
+    }
+
+    root("assessmentTest", "test", &quiz.metaline.title)
+        .child(outcome("SCORE", "float", 0))
+        .child(outcome("PASS", "boolean", false))
+        .child(part.child(body))
+        .child(
+            Xml::new("outcomeProcessing")
+                .child(
+                    Xml::new("setOutcomeValue")
+                        .attr("identifier", "SCORE")
+                        .child(sum("SCORE")),
+                )
+                .child(
+                    Xml::new("setOutcomeValue")
+                        .attr("identifier", "PASS")
+                        .child(
+                            Xml::new("gte")
+                                .child(
+                                    Xml::new("product")
+                                        .child(Xml::new("variable").attr("identifier", "SCORE"))
+                                        .child(base("float", total)),
+                                )
+                                .child(
+                                    Xml::new("product")
+                                        .child(base("float", quiz.metaline.pass))
+                                        .child(sum("MAXSCORE")),
+                                ),
+                        ),
+                ),
+        )
+}
+
+fn manifest(items: &[(String, &Question)]) -> Xml {
+    let resource = |id: &str, kind: &str, href: &str| {
+        Xml::new("resource")
+            .attr("identifier", id)
+            .attr("type", kind)
+            .attr("href", href)
+            .child(Xml::new("file").attr("href", href))
+    };
+
+    Xml::new("manifest")
+        .attr("xmlns", "http://www.imsglobal.org/xsd/imscp_v1p1")
+        .attr("identifier", "manifest")
+        .child(
+            Xml::new("metadata")
+                .child(Xml::new("schema").text("QTIv2.1 Package"))
+                .child(Xml::new("schemaversion").text("1.0.0")),
+        )
+        .child(Xml::new("organizations"))
+        .child(
+            Xml::new("resources")
+                .child(
+                    resource("test", "imsqti_test_xmlv2p1", "test.xml").children(
+                        items
+                            .iter()
+                            .map(|(id, _)| Xml::new("dependency").attr("identifierref", id)),
+                    ),
+                )
+                .children(items.iter().map(|(id, _)| {
+                    resource(id, "imsqti_item_xmlv2p1", &format!("items/{}.xml", id))
+                })),
+        )
+}
+
+pub fn qti(quiz: &Quiz, opts: &Options) -> Vec<u8> {
+    let items = quiz
+        .questions
+        .iter()
+        .filter(|q| opts.keeps(q))
+        .enumerate()
+        .map(|(n, q)| (format!("item-{}", n + 1), q))
+        .collect::<Vec<_>>();
+
+    let mut files = vec![
+        (
+            "imsmanifest.xml".to_string(),
+            manifest(&items).to_string().into_bytes(),
+        ),
+        (
+            "test.xml".to_string(),
+            test(quiz, &items).to_string().into_bytes(),
+        ),
+    ];
+    for (id, q) in &items {
+        files.push((
+            format!("items/{}.xml", id),
+            item(q, id).to_string().into_bytes(),
+        ));
+    }
+
+    zip::zip(&files)
+}
@@ -0,0 +1,455 @@
+"use strict";
+
+const quiz = JSON.parse(document.getElementById("data").textContent);
+const root = document.getElementById("quiz");
+
+function el(tag, attrs, ...children) {
+  const node = document.createElement(tag);
+  for (const [key, value] of Object.entries(attrs || {})) {
+    if (key.startsWith("on")) node.addEventListener(key.slice(2), value);
+    else node[key] = value;
+  }
+  node.append(...children.filter((c) => c !== null && c !== undefined));
+  return node;
+}
+
+function digest(text) {
+  let hash = 0x811c9dc5;
+  for (const byte of new TextEncoder().encode(quiz.salt + text)) {
+    hash = Math.imul(hash ^ byte, 0x01000193) >>> 0;
+  }
+  return hash.toString(16).padStart(8, "0");
+}
+
+const hidden = (text) => (quiz.salt === undefined ? text : digest(text));
+
+let state = BigInt(
+  quiz.seed ??
+    crypto.getRandomValues(new Uint32Array(2)).reduce((a, b) => (BigInt(a) << 32n) | BigInt(b), 0n),
+);
+
+function next64() {
+  state = BigInt.asUintN(64, state + 0x9e3779b97f4a7c15n);
+  let z = state;
+  z = BigInt.asUintN(64, (z ^ (z >> 30n)) * 0xbf58476d1ce4e5b9n);
+  z = BigInt.asUintN(64, (z ^ (z >> 27n)) * 0x94d049bb133111ebn);
+  return z ^ (z >> 31n);
+}
+
+function sample(indices, count) {
+  count = Math.min(count ?? indices.length, indices.length);
+  for (let i = 0; i < count; i++) {
+    const j = i + Number(next64() % BigInt(indices.length - i));
+    [indices[i], indices[j]] = [indices[j], indices[i]];
+  }
+  return indices.slice(0, count);
+}
+
+function draw() {
+  const all = quiz.questions.map((_, i) => i);
+  const pooled = (i) => {
+    const { section, pool } = quiz.questions[i];
+    return (section === undefined || quiz.sections[section].draw === undefined) && pool === undefined;
+  };
+
+  const drawn = sample(all.filter(pooled), quiz.draw);
+  quiz.sections.forEach((section, s) => {
+    if (section.draw !== undefined) {
+      drawn.push(...sample(all.filter((i) => quiz.questions[i].section === s), section.draw));
+    }
+  });
+  quiz.pools.forEach((count, p) => {
+    drawn.push(...sample(all.filter((i) => quiz.questions[i].pool === p), count));
+  });
+  return drawn.sort((a, b) => a - b);
+}
+
+function required(pass, drawn, total) {
+  if (drawn === total || total <= 0) return pass;
+  return Math.floor((pass * drawn + total - 1) / total);
+}
+
+function award(value, attempts) {
+  const decay = quiz.decay ?? 50;
+  for (let i = 1; i < attempts; i++) value = Math.ceil((value * decay) / 100);
+  return value;
+}
+
+function judge(question, answer) {
+  if (!question.choices) return question.answers.includes(hidden(answer));
+
+  const picked = answer.split(/[, ]/).filter((n) => n);
+  if (!picked.every((n) => /^\+?\d+$/.test(n))) return false;
+
+  const numbers = picked.map(Number);
+  if (numbers.length === 1) return question.answers.includes(hidden(String(numbers[0])));
+
+  const set = [...new Set(numbers)].sort((a, b) => a - b).join(",");
+  return question.set === hidden(set);
+}
+
+const drawn = draw();
+const marks = (section) =>
+  drawn
+    .map((i) => quiz.questions[i])
+    .filter((q) => section === undefined || q.section === section)
+    .reduce((sum, q) => sum + q.value, 0);
+
+const pass = required(quiz.pass, marks(), quiz.total);
+const sectionPass = quiz.sections.map((section, s) => required(section.pass, marks(s), section.total));
+const records = drawn.map((index) => ({
+  index,
+  answer: "",
+  verdict: "skipped",
+  points: 0,
+  attempts: 0,
+  time: 0,
+}));
+const shown = quiz.sections.map(() => false);
+
+let elapsed = 0;
+let clock = null;
+let current = null;
+let timer = null;
+
+const question = (pos) => quiz.questions[records[pos].index];
+const tries = (pos) => (quiz.exam ? 1 : question(pos).attempts ?? quiz.attempts ?? 1);
+const unanswered = () => records.filter((r) => r.verdict === "skipped").length;
+const expired = () => quiz.time !== undefined && elapsed >= quiz.time * 1000;
+
+function locked(pos) {
+  switch (records[pos].verdict) {
+    case "skipped":
+      return false;
+    case "timeout":
+      return true;
+    default:
+      return !quiz.exam;
+  }
+}
+
+function following(after) {
+  const pos = records.findIndex((r, p) => p > after && r.verdict === "skipped");
+  return pos < 0 ? submit : () => ask(pos);
+}
+
+function tally(filter) {
+  return records
+    .filter((r) => filter(quiz.questions[r.index]))
+    .reduce(([score, total], r) => [score + r.points, total + quiz.questions[r.index].value], [0, 0]);
+}
+
+function settle(pos, answer, verdict) {
+  const record = records[pos];
+  record.points = verdict === "correct" ? award(question(pos).value, record.attempts) : 0;
+  record.answer = answer;
+  record.verdict = verdict;
+}
+
+function spend() {
+  if (clock === null) return;
+  const now = Date.now();
+  elapsed += now - clock;
+  if (current !== null) records[current].time += now - clock;
+  clock = now;
+}
+
+function remaining() {
+  const left = [];
+  if (quiz.time !== undefined) left.push(quiz.time * 1000 - elapsed);
+  if (current !== null && question(current).time !== undefined) {
+    left.push(question(current).time * 1000 - records[current].time);
+  }
+  return left.length ? Math.max(0, Math.min(...left)) : null;
+}
+
+function wait(pos) {
+  current = pos;
+  clock = Date.now();
+}
+
+function halt() {
+  spend();
+  clock = null;
+  current = null;
+}
+
+function expire() {
+  halt();
+  for (const record of records) {
+    if (record.verdict === "skipped") record.verdict = "timeout";
+  }
+  done("time is up!");
+}
+
+function tick() {
+  spend();
+  if (clock === null) return;
+  if (expired()) return expire();
+
+  if (current !== null && remaining() === 0) {
+    const pos = current;
+    halt();
+    settle(pos, "", "timeout");
+    return quiz.exam ? following(pos)() : feedback(pos);
+  }
+
+  const left = remaining();
+  if (timer && left !== null) timer.textContent = `${Math.ceil(left / 1000)}s left`;
+}
+
+function progress(pos) {
+  let out = `question ${pos + 1}/${records.length}`;
+  if (!quiz.exam && !quiz.hide_score) {
+    const open = records
+      .filter((r) => r.verdict === "skipped")
+      .reduce((sum, r) => sum + quiz.questions[r.index].value, 0);
+    out += ` · score ${tally(() => true)[0]} · ${open} still possible`;
+  }
+  return out;
+}
+
+function show(...nodes) {
+  root.replaceChildren(
+    el("h1", {}, quiz.title),
+    el("p", { className: "by" }, `by ${quiz.by}`),
+    ...nodes,
+  );
+}
+
+function intro() {
+  const limit = quiz.time === undefined ? "" : ` · ${quiz.time}s time limit`;
+  show(
+    el("p", { className: "meta" }, `passing marks ${pass} · ${records.length} questions${limit}`),
+    el("button", { onclick: () => (records.length ? ask(0) : submit()) }, "start"),
+  );
+}
+
+function ask(pos) {
+  if (expired()) return expire();
+
+  const q = question(pos);
+  const record = records[pos];
+  const message = el("p", { className: "wrong" });
+  const picked = record.answer.split(",");
+  const inputs = q.choices
+    ? q.choices.map((choice, n) =>
+        el(
+          "label",
+          {},
+          el("input", {
+            type: "checkbox",
+            value: String(n + 1),
+            checked: picked.includes(String(n + 1)),
+          }),
+          ` ${choice}`,
+        ),
+      )
+    : [
+        el("input", {
+          type: "text",
+          autocomplete: "off",
+          value: record.answer,
+          onkeydown: (e) => e.key === "Enter" && answer(),
+        }),
+      ];
+
+  function answer() {
+    const text = q.choices
+      ? inputs
+          .map((label) => label.firstChild)
+          .filter((input) => input.checked)
+          .map((input) => input.value)
+          .join(",")
+      : inputs[0].value.trim();
+
+    if (locked(pos)) {
+      message.textContent = "this question is already answered";
+      return;
+    }
+
+    spend();
+    // revising an answer in exam mode isn't another attempt
+    if (!quiz.exam || record.attempts === 0) record.attempts += 1;
+    if (judge(q, text)) {
+      settle(pos, text, "correct");
+    } else if (record.attempts < tries(pos)) {
+      const left = tries(pos) - record.attempts;
+      message.textContent = `wrong answer! ${left} attempt${left === 1 ? "" : "s"} left`;
+      return;
+    } else {
+      settle(pos, text, "wrong");
+    }
+
+    halt();
+    if (quiz.exam) following(pos)();
+    else feedback(pos);
+  }
+
+  timer = el("p", { className: "meta" });
+  show(
+    el("p", { className: "meta" }, progress(pos)),
+    el(
+      "fieldset",
+      {},
+      el("legend", {}, `${q.text} `, el("span", { className: "value" }, `[${q.value}]`)),
+      ...inputs,
+    ),
+    timer,
+    message,
+    el("button", { onclick: answer, disabled: locked(pos) }, "answer"),
+    " ",
+    el("button", { onclick: () => (halt(), pos > 0 ? ask(pos - 1) : ask(pos)) }, "back"),
+    " ",
+    el("button", { onclick: () => (halt(), following(pos)()) }, "skip"),
+    " ",
+    el("button", { onclick: () => (halt(), submit()) }, "submit"),
+  );
+
+  wait(pos);
+  tick();
+  if (!q.choices) inputs[0].focus();
+}
+
+function subtotal(s) {
+  const [score, total] = tally((q) => q.section === s);
+  shown[s] = true;
+  return el(
+    "p",
+    { className: score >= sectionPass[s] ? "pass" : "fail" },
+    `${quiz.sections[s].name}: ${score} out of ${total}, passing marks ${sectionPass[s]}`,
+  );
+}
+
+function feedback(pos) {
+  const q = question(pos);
+  const record = records[pos];
+  const expected = q.expected === undefined ? null : el("p", { className: "meta" }, `expected ${q.expected}`);
+  const nodes = [];
+
+  switch (record.verdict) {
+    case "correct":
+      nodes.push(
+        el(
+          "p",
+          { className: "correct" },
+          record.points === q.value
+            ? "correct answer!"
+            : `correct answer! [${record.points} after ${record.attempts} attempts]`,
+        ),
+      );
+      break;
+    case "wrong":
+      nodes.push(el("p", { className: "wrong" }, "wrong answer!"), expected);
+      break;
+    default:
+      nodes.push(el("p", { className: "wrong" }, "timed out!"), expected);
+  }
+
+  const s = q.section;
+  if (
+    s !== undefined &&
+    !quiz.exam &&
+    records.every((r) => quiz.questions[r.index].section !== s || r.verdict !== "skipped")
+  ) {
+    nodes.push(subtotal(s));
+  }
+
+  show(
+    el("p", { className: "meta" }, progress(pos)),
+    el("fieldset", {}, el("legend", {}, q.text), ...nodes),
+    el("button", { onclick: () => following(pos)() }, "next"),
+  );
+}
+
+function submit() {
+  const left = unanswered();
+  if (left === 0) return done();
+
+  show(
+    el("p", {}, `you have ${left} unanswered question${left === 1 ? "" : "s"}, submit?`),
+    el("button", { onclick: () => (halt(), done()) }, "yes"),
+    " ",
+    el(
+      "button",
+      { onclick: () => (halt(), ask(records.findIndex((r) => r.verdict === "skipped"))) },
+      "no",
+    ),
+  );
+  wait(null);
+}
+
+function done(notice) {
+  clearInterval(interval);
+  halt();
+
+  const [score, total] = tally(() => true);
+  const failed = quiz.sections
+    .map((_, s) => s)
+    .filter((s) => tally((q) => q.section === s)[0] < sectionPass[s]);
+  const passed = score >= pass && (!quiz.strict || failed.length === 0);
+  const nodes = [];
+
+  if (notice) nodes.push(el("p", { className: "fail" }, notice));
+
+  if (quiz.exam) {
+    const table = el(
+      "table",
+      {},
+      el("tr", {}, ...["#", "question", "answer", "expected", "points"].map((h) => el("th", {}, h))),
+    );
+    records.forEach((record, n) => {
+      const q = quiz.questions[record.index];
+      const answer =
+        record.verdict === "timeout"
+          ? "(timed out)"
+          : record.verdict === "skipped"
+            ? "(skipped)"
+            : q.choices
+              ? record.answer
+                  .split(",")
+                  .map((n) => q.choices[n - 1])
+                  .join(", ")
+              : record.answer;
+      table.append(
+        el(
+          "tr",
+          { className: record.verdict === "correct" ? "correct" : "wrong" },
+          ...[n + 1, q.text, answer, q.expected ?? "", `${record.points}/${q.value}`].map((c) =>
+            el("td", {}, String(c)),
+          ),
+        ),
+      );
+    });
+    nodes.push(table);
+  }
+
+  quiz.sections.forEach((_, s) => {
+    if (!shown[s] && records.some((r) => quiz.questions[r.index].section === s)) {
+      nodes.push(subtotal(s));
+    }
+  });
+
+  nodes.push(
+    el(
+      "h2",
+      { className: passed ? "pass" : "fail" },
+      `you scored ${score} out of ${total}, you ${passed ? "passed" : "failed"}!`,
+    ),
+    el("p", { className: "meta" }, `passing marks ${pass}`),
+  );
+  if (failed.length) {
+    nodes.push(
+      el(
+        "p",
+        { className: "fail" },
+        `sections not passed: ${failed.map((s) => quiz.sections[s].name).join(", ")}`,
+      ),
+    );
+  }
+
+  show(...nodes);
+}
+
+const interval = setInterval(tick, 200);
+intro();
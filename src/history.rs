@@ -0,0 +1,229 @@
+use crate::{
+    ast::Quiz,
+    fatal,
+    run::Verdict,
+    session::{Session, Summary},
+    utils::{Color, Exit, color, data_dir, date, hash, now},
+};
+use std::io::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub time: u64,
+    pub hash: u64,
+    pub score: isize,
+    pub total: isize,
+    pub passed: bool,
+    pub questions: Vec<(u64, Verdict)>,
+    pub file: String,
+}
+
+impl Entry {
+    pub fn new(session: &Session, summary: &Summary) -> Self {
+        Self {
+            time: now(),
+            hash: session.hash,
+            score: summary.score,
+            total: summary.total,
+            passed: summary.passed,
+            questions: session
+                .records
+                .iter()
+                .map(|r| (hash(&session.quiz.questions[r.index].text), r.verdict))
+                .collect(),
+            file: session.file.to_string(),
+        }
+    }
+
+    pub fn line(&self) -> String {
+        format!(
+            "{}\t{:016x}\t{}\t{}\t{}\t{}\t{}",
+            self.time,
+            self.hash,
+            self.score,
+            self.total,
+            self.passed,
+            self.questions
+                .iter()
+                .map(|(hash, verdict)| format!("{:016x}:{}", hash, verdict.name()))
+                .collect::<Vec<_>>()
+                .join(","),
+            self.file
+        )
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(7, '\t');
+
+        Some(Self {
+            time: fields.next()?.parse().ok()?,
+            hash: u64::from_str_radix(fields.next()?, 16).ok()?,
+            score: fields.next()?.parse().ok()?,
+            total: fields.next()?.parse().ok()?,
+            passed: fields.next()?.parse().ok()?,
+            questions: fields
+                .next()?
+                .split(',')
+                .filter(|q| !q.is_empty())
+                .map(|q| {
+                    let (hash, verdict) = q.split_once(':')?;
+                    Some((
+                        u64::from_str_radix(hash, 16).ok()?,
+                        Verdict::from_name(verdict)?,
+                    ))
+                })
+                .collect::<Option<_>>()?,
+            file: fields.next()?.to_string(),
+        })
+    }
+}
+
+pub fn append(session: &Session, summary: &Summary) {
+    let Some(dir) = data_dir() else {
+        eprintln!(
+            "{}",
+            color(
+                Color::Yellow,
+                "no data directory found, set XDG_DATA_HOME or HOME to keep a score history"
+            )
+        );
+        return;
+    };
+    let entry = Entry::new(session, summary);
+
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join("history"))
+        })
+        .and_then(|mut file| writeln!(file, "{}", entry.line()))
+        .unwrap_or_else(|e| {
+            fatal!(
+                Exit::Io;
+                "{}",
+                color(Color::Red, &format!("failed to write history: {}", e))
+            )
+        });
+}
+
+pub fn load(file: &str) -> Vec<Entry> {
+    let Some(dir) = data_dir() else {
+        return Vec::new();
+    };
+
+    match std::fs::read_to_string(dir.join("history")) {
+        Ok(text) => text
+            .lines()
+            .filter_map(Entry::parse)
+            .filter(|e| e.file == file)
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => fatal!(
+            Exit::Io;
+            "{}",
+            color(Color::Red, &format!("failed to read history: {}", e))
+        ),
+    }
+}
+
+pub fn missed(quiz: &Quiz, entries: &[Entry]) -> Vec<(usize, usize, usize)> {
+    let mut missed = quiz
+        .questions
+        .iter()
+        .enumerate()
+        .map(|(index, question)| {
+            let id = hash(&question.text);
+            let verdicts = entries
+                .iter()
+                .flat_map(|e| &e.questions)
+                .filter(|(hash, _)| *hash == id);
+
+            (
+                index,
+                verdicts.clone().count(),
+                verdicts.filter(|(_, v)| *v != Verdict::Correct).count(),
+            )
+        })
+        .filter(|&(_, _, missed)| missed > 0)
+        .collect::<Vec<_>>();
+
+    missed.sort_by(|a, b| b.2.cmp(&a.2).then(a.1.cmp(&b.1)));
+    missed
+}
+
+pub fn stats(quiz: &Quiz, file: &str, text: &str) {
+    let file = std::fs::canonicalize(file)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file.to_string());
+    let current = hash(text);
+    let entries = load(&file);
+
+    println!(
+        "{}\n{} {}\n",
+        color(Color::Yellow, &quiz.metaline.title),
+        color(Color::Grey, "by"),
+        color(Color::Yellow, &quiz.metaline.by)
+    );
+
+    if entries.is_empty() {
+        println!("{}", color(Color::Grey, "no runs recorded yet"));
+        return;
+    }
+
+    let best = entries
+        .iter()
+        .max_by_key(|e| (e.score, -e.total))
+        .unwrap_or_else(|| unreachable!());
+    let average = entries.iter().map(|e| e.score as f64).sum::<f64>() / entries.len() as f64;
+    let passed = entries.iter().filter(|e| e.passed).count();
+
+    println!(
+        "{} {}  {} {}  {} {}  {} {}\n",
+        color(Color::Grey, "runs"),
+        color(Color::SuperCyan, &entries.len().to_string()),
+        color(Color::Grey, "best"),
+        color(Color::SuperCyan, &format!("{}/{}", best.score, best.total)),
+        color(Color::Grey, "average"),
+        color(Color::SuperCyan, &format!("{:.1}", average)),
+        color(Color::Grey, "pass rate"),
+        color(
+            Color::SuperCyan,
+            &format!("{}%", passed * 100 / entries.len())
+        )
+    );
+
+    for entry in &entries {
+        println!(
+            "{} {} {}{}",
+            color(Color::Grey, &date(entry.time)),
+            color(
+                Color::SuperCyan,
+                &format!("{}/{}", entry.score, entry.total)
+            ),
+            if entry.passed {
+                color(Color::Green, "passed")
+            } else {
+                color(Color::Red, "failed")
+            },
+            if entry.hash == current {
+                String::new()
+            } else {
+                color(Color::Grey, " (earlier version)")
+            }
+        );
+    }
+
+    let missed = missed(quiz, &entries);
+    if !missed.is_empty() {
+        println!("\n{}", color(Color::Yellow, "most missed"));
+        for (index, asked, missed) in missed.into_iter().take(5) {
+            println!(
+                "{} {}",
+                color(Color::Red, &format!("{:>3} of {:<3}", missed, asked)),
+                color(Color::SuperCyan, &quiz.questions[index].text)
+            );
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use crate::{
+    ast::{Question, Quiz},
+    fatal,
+    party::{self, Player},
+    run,
+    utils::{Color, Exit, Rng, color, duration},
+};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
+use std::time::{Duration, Instant};
+
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub enum Event {
+    Connect(usize, TcpStream),
+    Line(usize, String),
+    Leave(usize),
+    Host(String),
+}
+
+struct Client {
+    stream: TcpStream,
+    player: Option<usize>,
+    connected: bool,
+}
+
+struct Round {
+    started: Instant,
+    answers: Vec<Option<(String, Duration)>>,
+}
+
+struct Game<'a> {
+    quiz: &'a Quiz,
+    setup: &'a party::Options,
+    clients: Vec<Client>,
+    players: Vec<Player>,
+    quit: bool,
+}
+
+impl Game<'_> {
+    fn send(&mut self, id: usize, text: &str) {
+        let client = &mut self.clients[id];
+        if client.stream.write_all(text.as_bytes()).is_err() {
+            client.connected = false;
+            let _ = client.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn broadcast(&mut self, text: &str) {
+        for id in 0..self.clients.len() {
+            if self.clients[id].connected && self.clients[id].player.is_some() {
+                self.send(id, text);
+            }
+        }
+    }
+
+    fn answered(&self, round: &Round) -> bool {
+        self.clients
+            .iter()
+            .filter(|c| c.connected)
+            .filter_map(|c| c.player)
+            .all(|p| round.answers[p].is_some())
+    }
+
+    fn leaderboard(&self) -> String {
+        let mut out = String::from("leaderboard\n");
+
+        for (rank, p) in party::ranking(&self.players, self.setup.tie_break) {
+            let player = &self.players[p];
+            out += &format!(
+                "{:>3}. {} {} ({} correct, {})\n",
+                rank,
+                player.name,
+                player.score,
+                player.correct,
+                duration(player.time.as_secs())
+            );
+        }
+
+        out
+    }
+
+    fn handle(&mut self, event: Event, round: Option<&mut Round>) {
+        match event {
+            Event::Connect(id, stream) => {
+                self.clients.push(Client {
+                    stream,
+                    player: None,
+                    connected: true,
+                });
+                let quiz = self.quiz;
+                self.send(
+                    id,
+                    &format!(
+                        "welcome to {} by {}\nyour name? ",
+                        quiz.metaline.title, quiz.metaline.by
+                    ),
+                );
+            }
+            Event::Line(id, line) => {
+                let line = line.trim();
+
+                let Some(p) = self.clients[id].player else {
+                    if line.is_empty() || self.players.iter().any(|p| p.name == line) {
+                        self.send(id, "that name is empty or taken\nyour name? ");
+                        return;
+                    }
+
+                    self.players.push(Player {
+                        name: line.to_string(),
+                        ..Default::default()
+                    });
+                    self.clients[id].player = Some(self.players.len() - 1);
+                    if let Some(round) = round {
+                        round.answers.push(None);
+                    }
+
+                    println!(
+                        "{} {}",
+                        color(Color::Yellow, line),
+                        color(Color::Grey, "joined")
+                    );
+                    self.send(id, "you're in! wait for the next question\n");
+                    self.broadcast(&format!(
+                        "{} joined, {} playing\n",
+                        line,
+                        self.players.len()
+                    ));
+                    return;
+                };
+
+                match round {
+                    Some(round) if round.answers[p].is_none() => {
+                        round.answers[p] = Some((line.to_string(), round.started.elapsed()));
+                        self.send(id, "answer received\n");
+                    }
+                    Some(_) => self.send(id, "you already answered\n"),
+                    None => self.send(id, "wait for the next question\n"),
+                }
+            }
+            Event::Leave(id) => {
+                self.clients[id].connected = false;
+                if let Some(p) = self.clients[id].player {
+                    println!(
+                        "{} {}",
+                        color(Color::Yellow, &self.players[p].name),
+                        color(Color::Grey, "left")
+                    );
+                }
+            }
+            Event::Host(line) => {
+                if line.trim() == ":quit" {
+                    self.quit = true;
+                }
+            }
+        }
+    }
+
+    fn ask(&mut self, question: &Question, n: usize, total: usize, rx: &Receiver<Event>) {
+        let limit = Duration::from_secs(question.time.or(self.setup.deadline).unwrap_or(30));
+        let deadline = Instant::now() + limit;
+
+        let mut text = format!(
+            "\nquestion {}/{} [{}]\n{}\n",
+            n + 1,
+            total,
+            question.value,
+            question.text
+        );
+        if !question.answer.options.is_empty() {
+            for (index, answer) in question.answer.answers.iter().enumerate() {
+                text += &format!("{} {}\n", index + 1, answer);
+            }
+        }
+        text += &format!("answer within {}\n> ", duration(limit.as_secs()));
+        self.broadcast(&text);
+
+        println!(
+            "{}",
+            color(
+                Color::Grey,
+                &format!("question {}/{} [{}]", n + 1, total, question.value)
+            )
+        );
+        run::show(question);
+
+        let mut round = Round {
+            started: Instant::now(),
+            answers: vec![None; self.players.len()],
+        };
+
+        while !self.quit && !self.answered(&round) {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(event) => self.handle(event, Some(&mut round)),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let expected = run::expected(question);
+        for id in 0..self.clients.len() {
+            let Some(p) = self.clients[id].player else {
+                continue;
+            };
+
+            let reply = match &round.answers[p] {
+                Some((answer, time)) => {
+                    self.players[p].time += *time;
+                    if run::judge(question, answer) {
+                        self.players[p].score += question.value;
+                        self.players[p].correct += 1;
+                        format!("correct! +{}\n", question.value)
+                    } else {
+                        format!("wrong! expected {}\n", expected)
+                    }
+                }
+                None => {
+                    self.players[p].time += limit;
+                    format!("time is up! expected {}\n", expected)
+                }
+            };
+
+            if self.clients[id].connected {
+                self.send(id, &reply);
+            }
+        }
+
+        println!(
+            "{}\n",
+            color(
+                Color::Grey,
+                &format!(
+                    "└─ {} of {} answered, expected {}",
+                    round.answers.iter().filter(|a| a.is_some()).count(),
+                    round.answers.len(),
+                    expected
+                )
+            )
+        );
+        party::board(&self.players, self.setup.tie_break, false);
+        println!();
+
+        let board = self.leaderboard();
+        self.broadcast(&board);
+    }
+}
+
+pub fn accept(incoming: impl Iterator<Item = std::io::Result<TcpStream>>, tx: Sender<Event>) {
+    let mut id = 0;
+
+    for stream in incoming {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        if writer.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+            continue;
+        }
+        if tx.send(Event::Connect(id, writer)).is_err() {
+            break;
+        }
+
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if tx.send(Event::Line(id, line)).is_err() {
+                    return;
+                }
+            }
+            let _ = tx.send(Event::Leave(id));
+        });
+        id += 1;
+    }
+}
+
+pub fn serve(
+    quiz: &Quiz,
+    opts: &run::Options,
+    setup: &party::Options,
+    listener: TcpListener,
+    (tx, rx): (Sender<Event>, Receiver<Event>),
+) -> (bool, Vec<Player>) {
+    let seed = opts.seed.unwrap_or_else(Rng::seed);
+    let drawn = run::draw(quiz, opts, &mut Rng::new(seed));
+    if drawn.is_empty() {
+        fatal!(Exit::Quiz; "{}", color(Color::Red, "no questions left to ask"));
+    }
+
+    std::thread::spawn(move || accept(listener.incoming(), tx));
+
+    let mut game = Game {
+        quiz,
+        setup,
+        clients: Vec::new(),
+        players: Vec::new(),
+        quit: false,
+    };
+
+    while !game.quit && setup.expect.is_none_or(|n| game.players.len() < n) {
+        match rx.recv() {
+            Ok(Event::Host(line)) if line.trim() != ":quit" => {
+                if game.players.is_empty() {
+                    println!("{}", color(Color::Red, "no players have joined yet"));
+                } else {
+                    break;
+                }
+            }
+            Ok(event) => game.handle(event, None),
+            Err(_) => game.quit = true,
+        }
+    }
+
+    if game.quit {
+        for client in &game.clients {
+            let _ = client.stream.shutdown(Shutdown::Both);
+        }
+        return (false, game.players);
+    }
+
+    println!(
+        "\n{}\n",
+        color(
+            Color::Grey,
+            &format!("starting with {} players", game.players.len())
+        )
+    );
+    game.broadcast("\nthe quiz is starting!\n");
+
+    for (n, &index) in drawn.iter().enumerate() {
+        if game.quit {
+            break;
+        }
+        game.ask(&quiz.questions[index], n, drawn.len(), &rx);
+    }
+
+    let finished = !game.quit;
+    println!(
+        "{}",
+        color(
+            Color::Yellow,
+            if finished {
+                "final leaderboard"
+            } else {
+                "game ended early"
+            }
+        )
+    );
+    party::board(&game.players, setup.tie_break, true);
+
+    let board = format!("\nfinal {}thanks for playing!\n", game.leaderboard());
+    game.broadcast(&board);
+    for client in &game.clients {
+        let _ = client.stream.shutdown(Shutdown::Both);
+    }
+
+    (finished, game.players)
+}
+
+pub fn host(quiz: &Quiz, opts: &run::Options, setup: &party::Options) -> Exit {
+    let Some(address) = &setup.listen else {
+        fatal!("{}", color(Color::Red, "expected --listen with host"));
+    };
+
+    let listener = TcpListener::bind(address).unwrap_or_else(|e| {
+        fatal!(
+            Exit::Io;
+            "{}",
+            color(Color::Red, &format!("failed to listen on {}: {}", address, e))
+        )
+    });
+
+    println!(
+        "{}\n{} {}\n{} {}",
+        color(Color::Yellow, &quiz.metaline.title),
+        color(Color::Grey, "by"),
+        color(Color::Yellow, &quiz.metaline.by),
+        color(Color::Grey, "listening on"),
+        color(
+            Color::Yellow,
+            &listener
+                .local_addr()
+                .map_or(address.to_string(), |a| a.to_string())
+        )
+    );
+    println!(
+        "{}\n",
+        color(
+            Color::Grey,
+            &match setup.expect {
+                Some(n) => format!("starting once {} players have joined", n),
+                None => "press enter to start, or type :quit to end the game".to_string(),
+            }
+        )
+    );
+
+    let (tx, rx) = channel();
+    let host = tx.clone();
+    let lines = run::lines();
+    std::thread::spawn(move || {
+        while let Ok(line) = lines.recv() {
+            if host.send(Event::Host(line)).is_err() {
+                break;
+            }
+        }
+    });
+
+    if serve(quiz, opts, setup, listener, (tx, rx)).0 {
+        Exit::Passed
+    } else {
+        Exit::Aborted
+    }
+}
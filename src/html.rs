@@ -0,0 +1,34 @@
+pub const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:46rem;margin:2rem auto;padding:0 1rem;background:#111;color:#ddd}\
+h1{color:#e5c07b;margin-bottom:0}.by,.meta{color:#888}\
+fieldset{border:1px solid #333;border-radius:.5rem;margin:1rem 0;padding:1rem}\
+legend{color:#56b6c2}.value{color:#888}label{display:block;margin:.25rem 0}\
+input[type=text]{width:100%;box-sizing:border-box;padding:.4rem;background:#222;color:#ddd;border:1px solid #444}\
+button{padding:.5rem 1.5rem;background:#56b6c2;border:0;border-radius:.25rem;color:#111;font-size:1rem}\
+table{border-collapse:collapse;width:100%}td,th{border-bottom:1px solid #333;padding:.3rem;text-align:left}\
+.pass,.correct{color:#98c379}.fail,.wrong{color:#e06c75}";
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for chr in text.chars() {
+        match chr {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+pub fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
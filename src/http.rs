@@ -0,0 +1,445 @@
+use crate::{
+    ast::{Question, Quiz},
+    fatal, html, report,
+    run::{Options, Verdict},
+    session::Session,
+    utils::{Color, Exit, Rng, color},
+};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const PENDING_LIMIT: usize = 10_000;
+const PENDING_AGE: Duration = Duration::from_secs(12 * 60 * 60);
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: &'static str,
+    pub kind: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn html(title: &str, body: &str) -> Self {
+        Self {
+            status: "200 OK",
+            kind: "text/html; charset=utf-8",
+            body: html::page(title, body),
+        }
+    }
+
+    fn error(status: &'static str) -> Self {
+        Self {
+            status,
+            kind: "text/plain; charset=utf-8",
+            body: status.to_string() + "\n",
+        }
+    }
+}
+
+pub fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match (
+                    (bytes[i + 1] as char).to_digit(16),
+                    (bytes[i + 2] as char).to_digit(16),
+                ) {
+                    (Some(high), Some(low)) => {
+                        out.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+pub fn form(body: &str) -> Vec<(String, String)> {
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn numbered(path: &str, n: usize) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path
+        .file_stem()
+        .map_or(String::new(), |s| s.to_string_lossy().into_owned());
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, n),
+    };
+
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn shown(question: &Question, answer: &str) -> String {
+    if question.answer.options.is_empty() {
+        return answer.to_string();
+    }
+
+    answer
+        .split(',')
+        .filter_map(|n| n.parse::<usize>().ok())
+        .filter_map(|n| question.answer.answers.get(n.wrapping_sub(1)).cloned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn token() -> String {
+    let nanos = Rng::seed();
+    format!(
+        "{:016x}{:016x}",
+        RandomState::new().hash_one(nanos),
+        RandomState::new().hash_one(nanos)
+    )
+}
+
+pub struct Server<'a> {
+    pub quiz: &'a Quiz,
+    pub opts: &'a Options,
+    pub pending: Mutex<HashMap<String, (u64, Instant)>>,
+    pub submissions: Mutex<usize>,
+}
+
+impl Server<'_> {
+    fn heading(&self, session: &Session) -> String {
+        let quiz = self.quiz;
+        format!(
+            "<h1>{}</h1>\n<p class=\"by\">by {}</p>\n<p class=\"meta\">passing marks {} · {} questions</p>\n",
+            html::escape(&quiz.metaline.title),
+            html::escape(&quiz.metaline.by),
+            session.pass,
+            session.records.len()
+        )
+    }
+
+    fn quiz(&self) -> Response {
+        let seed = self.opts.seed.unwrap_or_else(Rng::seed);
+        let session = Session::new(
+            self.quiz,
+            &Options {
+                seed: Some(seed),
+                ..self.opts.clone()
+            },
+        );
+
+        let token = token();
+        {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.retain(|_, (_, issued)| issued.elapsed() < PENDING_AGE);
+            if pending.len() >= PENDING_LIMIT
+                && let Some(oldest) = pending
+                    .iter()
+                    .min_by_key(|(_, (_, issued))| *issued)
+                    .map(|(token, _)| token.clone())
+            {
+                pending.remove(&oldest);
+            }
+            pending.insert(token.clone(), (seed, Instant::now()));
+        }
+
+        let mut body = self.heading(&session);
+        body += &format!(
+            "<form method=\"post\" action=\"/submit\">\n<input type=\"hidden\" name=\"token\" value=\"{}\">\n<label>your name <input type=\"text\" name=\"name\" required></label>\n",
+            token
+        );
+
+        for (n, record) in session.records.iter().enumerate() {
+            let question = &self.quiz.questions[record.index];
+            body += &format!(
+                "<fieldset>\n<legend>{}. {} <span class=\"value\">[{}]</span></legend>\n",
+                n + 1,
+                html::escape(&question.text),
+                question.value
+            );
+
+            if question.answer.options.is_empty() {
+                body += &format!(
+                    "<input type=\"text\" name=\"q{}\" autocomplete=\"off\">\n",
+                    n
+                );
+            } else {
+                for (index, answer) in question.answer.answers.iter().enumerate() {
+                    body += &format!(
+                        "<label><input type=\"checkbox\" name=\"q{}\" value=\"{}\"> {}</label>\n",
+                        n,
+                        index + 1,
+                        html::escape(answer)
+                    );
+                }
+            }
+
+            body += "</fieldset>\n";
+        }
+
+        body += "<button type=\"submit\">submit</button>\n</form>\n";
+        Response::html(&self.quiz.metaline.title, &body)
+    }
+
+    fn submit(&self, request: &Request) -> Response {
+        let fields = form(&request.body);
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let (Some(token), Some(name)) = (field("token"), field("name")) else {
+            return Response::error("400 Bad Request");
+        };
+        let Some(seed) = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(token)
+            .map(|(seed, _)| seed)
+        else {
+            return Response {
+                status: "409 Conflict",
+                ..Response::html(
+                    &self.quiz.metaline.title,
+                    "<p>this quiz was already submitted or has expired. <a href=\"/\">take it again</a></p>\n",
+                )
+            };
+        };
+
+        let quiz = self.quiz;
+        let mut session = Session::new(
+            quiz,
+            &Options {
+                seed: Some(seed),
+                ..self.opts.clone()
+            },
+        );
+        let answers = (0..session.records.len())
+            .map(|n| {
+                let name = format!("q{}", n);
+                let values = fields
+                    .iter()
+                    .filter(|(k, v)| *k == name && !v.trim().is_empty())
+                    .map(|(_, v)| v.trim())
+                    .collect::<Vec<_>>();
+                (!values.is_empty()).then(|| values.join(","))
+            })
+            .collect();
+
+        session.student = Some(name.to_string());
+        session.grade(answers);
+        let summary = session.summary();
+
+        let n = {
+            let mut submissions = self.submissions.lock().unwrap_or_else(|e| e.into_inner());
+            *submissions += 1;
+            *submissions
+        };
+
+        if let Some((format, path)) = &self.opts.report {
+            report::write(&session, &summary, *format, &numbered(path, n));
+        }
+
+        println!(
+            "{} {} {} {}",
+            color(Color::Grey, &format!("submission {}:", n)),
+            color(Color::Yellow, name),
+            color(
+                Color::SuperCyan,
+                &format!("{}/{}", summary.score, summary.total)
+            ),
+            if summary.passed {
+                color(Color::Green, "passed")
+            } else {
+                color(Color::Red, "failed")
+            }
+        );
+
+        let mut body = self.heading(&session);
+        body += &format!(
+            "<h2 class=\"{}\">you scored {} out of {}, you {}!</h2>\n",
+            if summary.passed { "pass" } else { "fail" },
+            summary.score,
+            summary.total,
+            if summary.passed { "passed" } else { "failed" }
+        );
+        body += "<table>\n<tr><th>#</th><th>question</th><th>answer</th><th>points</th></tr>\n";
+
+        for (index, record) in session.records.iter().enumerate() {
+            let question = &quiz.questions[record.index];
+            body += &format!(
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}/{}</td></tr>\n",
+                if record.verdict == Verdict::Correct {
+                    "correct"
+                } else {
+                    "wrong"
+                },
+                index + 1,
+                html::escape(&question.text),
+                html::escape(&shown(question, &record.answer)),
+                record.points,
+                question.value
+            );
+        }
+
+        body += "</table>\n<p><a href=\"/\">take the quiz again</a></p>\n";
+        Response::html(&quiz.metaline.title, &body)
+    }
+
+    pub fn respond(&self, request: &Request) -> Response {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/") => self.quiz(),
+            ("POST", "/submit") => self.submit(request),
+            (_, "/" | "/submit") => Response::error("405 Method Not Allowed"),
+            _ => Response::error("404 Not Found"),
+        }
+    }
+}
+
+const BAD_REQUEST: &str = "400 Bad Request";
+const TOO_LARGE: &str = "431 Request Header Fields Too Large";
+pub const LINE_LIMIT: usize = 8192;
+pub const HEADER_LIMIT: usize = 100;
+
+fn line(reader: &mut impl BufRead, status: &'static str) -> Result<String, &'static str> {
+    let mut line = String::new();
+    match reader
+        .by_ref()
+        .take(LINE_LIMIT as u64 + 1)
+        .read_line(&mut line)
+    {
+        Ok(0) | Err(_) => Err(BAD_REQUEST),
+        Ok(_) if line.len() > LINE_LIMIT => Err(status),
+        Ok(_) => Ok(line),
+    }
+}
+
+pub fn read(stream: impl Read) -> Result<Request, &'static str> {
+    let mut reader = BufReader::new(stream);
+    let request = line(&mut reader, BAD_REQUEST)?;
+
+    let mut parts = request.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(BAD_REQUEST);
+    };
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    let mut length = 0;
+    let mut headers = 0;
+
+    loop {
+        let line = line(&mut reader, TOO_LARGE)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        headers += 1;
+        if headers > HEADER_LIMIT {
+            return Err(TOO_LARGE);
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse().map_err(|_| BAD_REQUEST)?;
+        }
+    }
+
+    if length > 1 << 20 {
+        return Err(BAD_REQUEST);
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|_| BAD_REQUEST)?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn reply(mut stream: &TcpStream, response: &Response) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.kind,
+        response.body.len(),
+        response.body
+    );
+    let _ = stream.flush();
+}
+
+pub fn serve(quiz: &Quiz, opts: &Options, port: Option<u16>) -> Exit {
+    let address = format!("0.0.0.0:{}", port.unwrap_or(8080));
+    let listener = TcpListener::bind(&address).unwrap_or_else(|e| {
+        fatal!(
+            Exit::Io;
+            "{}",
+            color(Color::Red, &format!("failed to listen on {}: {}", address, e))
+        )
+    });
+
+    if !quiz.questions.is_empty() && !quiz.questions.iter().any(|q| opts.keeps(q)) {
+        fatal!(Exit::Quiz; "{}", color(Color::Red, "no questions left to ask"));
+    }
+    println!(
+        "{}\n{} {}\n{} {}\n",
+        color(Color::Yellow, &quiz.metaline.title),
+        color(Color::Grey, "by"),
+        color(Color::Yellow, &quiz.metaline.by),
+        color(Color::Grey, "serving on"),
+        color(Color::Yellow, &format!("http://{}/", address))
+    );
+
+    let server = Server {
+        quiz,
+        opts,
+        pending: Mutex::new(HashMap::new()),
+        submissions: Mutex::new(0),
+    };
+
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let server = &server;
+
+            scope.spawn(move || {
+                let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+                let response = match read(&stream) {
+                    Ok(request) => server.respond(&request),
+                    Err(status) => Response::error(status),
+                };
+                reply(&stream, &response);
+            });
+        }
+    });
+
+    Exit::Passed
+}
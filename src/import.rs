@@ -0,0 +1,48 @@
+use crate::{
+    ast::Quiz,
+    export::qq,
+    utils::{Color, Exit, color},
+};
+
+pub mod gift;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Gift,
+}
+
+impl Source {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gift" => Some(Source::Gift),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+pub fn parse(file: &str, text: &str, source: Source) -> (Quiz, Vec<Diagnostic>) {
+    match source {
+        Source::Gift => gift::parse(file, text),
+    }
+}
+
+pub fn import(file: &str, text: &str, source: Source) -> Exit {
+    let (quiz, diagnostics) = parse(file, text, source);
+
+    for diagnostic in &diagnostics {
+        eprintln!(
+            "{} {}",
+            color(Color::Yellow, &format!("{}:{}:", file, diagnostic.line)),
+            diagnostic.message
+        );
+    }
+
+    print!("{}", qq::qq(&quiz));
+    Exit::Passed
+}
@@ -0,0 +1,536 @@
+use crate::{
+    ast::{Answer, Mode, Pool, Question, Quiz, Section},
+    import::Diagnostic,
+};
+
+type Chars = Vec<(char, bool)>;
+
+fn chars(text: &str) -> Chars {
+    let mut out = Vec::with_capacity(text.len());
+    let mut iter = text.chars();
+
+    while let Some(chr) = iter.next() {
+        match chr {
+            '\\' => match iter.next() {
+                Some('n') => out.push(('\n', true)),
+                Some(c) => out.push((c, true)),
+                None => out.push(('\\', true)),
+            },
+            c => out.push((c, false)),
+        }
+    }
+
+    out
+}
+
+fn text(chars: &[(char, bool)]) -> String {
+    chars
+        .iter()
+        .map(|&(c, escaped)| if c == '\n' && !escaped { ' ' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn find(chars: &[(char, bool)], want: &str) -> Option<usize> {
+    let want = want.chars().collect::<Vec<_>>();
+    (0..chars.len()).find(|&i| {
+        i + want.len() <= chars.len()
+            && want
+                .iter()
+                .enumerate()
+                .all(|(j, &c)| chars[i + j] == (c, false))
+    })
+}
+
+fn words(text: &str) -> Vec<(String, bool)> {
+    let mut out = Vec::new();
+    let mut iter = text.chars().peekable();
+
+    while let Some(chr) = iter.next() {
+        match chr {
+            '"' => {
+                let mut word = String::new();
+                while let Some(c) = iter.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => word.extend(iter.next()),
+                        c => word.push(c),
+                    }
+                }
+                out.push((word, true));
+            }
+            c if c.is_whitespace() || c == ',' => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(&c) = iter.peek()
+                    && !c.is_whitespace()
+                    && c != ','
+                {
+                    word.push(c);
+                    iter.next();
+                }
+                out.push((word, false));
+            }
+        }
+    }
+
+    out
+}
+
+fn duration(word: &str) -> Option<u64> {
+    let (number, scale) = match word.chars().last()? {
+        's' => (&word[..word.len() - 1], 1),
+        'm' => (&word[..word.len() - 1], 60),
+        'h' => (&word[..word.len() - 1], 3600),
+        _ => (word, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .filter(|&n| n > 0)
+        .map(|n| n * scale)
+}
+
+#[derive(Default)]
+struct Parser {
+    quiz: Quiz,
+    diagnostics: Vec<Diagnostic>,
+    line: usize,
+    section: Option<usize>,
+    pending: Option<Question>,
+    titled: bool,
+}
+
+impl Parser {
+    fn warn(&mut self, message: &str) {
+        self.diagnostics.push(Diagnostic {
+            line: self.line,
+            message: message.to_string(),
+        });
+    }
+
+    fn string(&mut self, text: String) -> String {
+        if text.contains('"') {
+            self.warn(
+                "double quotes aren't supported in .qq strings, replaced them with single quotes",
+            );
+            text.replace('"', "'")
+        } else {
+            text
+        }
+    }
+
+    fn meta(&mut self, line: &str) {
+        let words = words(line);
+        let mut iter = words.iter().map(|(w, quoted)| (w.as_str(), *quoted));
+        let mut question = Question {
+            value: 1,
+            ..Default::default()
+        };
+        let mut bad = false;
+
+        match iter.next() {
+            Some(("title", false)) => {
+                let mut next = || iter.next().map(|(w, _)| w.to_string());
+                match (next(), next().as_deref(), next(), next().as_deref(), next()) {
+                    (Some(title), Some("by"), Some(by), Some("pass"), Some(pass))
+                        if pass.parse::<isize>().is_ok() =>
+                    {
+                        self.quiz.metaline.title = self.string(title);
+                        self.quiz.metaline.by = self.string(by);
+                        self.quiz.metaline.pass = pass.parse().unwrap_or_default();
+                        self.titled = true;
+                    }
+                    _ => bad = true,
+                }
+            }
+            Some(("draw", false)) => {
+                match (
+                    iter.next().and_then(|(w, _)| w.parse().ok()),
+                    iter.next(),
+                    iter.next(),
+                ) {
+                    (Some(n), None, _) if n > 0 => self.quiz.draw = Some(n),
+                    (Some(n), Some(("from", false)), Some((tag, true)))
+                        if n > 0 && !self.quiz.pools.iter().any(|p| p.tag == tag) =>
+                    {
+                        let tag = self.string(tag.to_string());
+                        self.quiz.pools.push(Pool { tag, draw: n })
+                    }
+                    _ => bad = true,
+                }
+            }
+            Some(("strict", false)) => self.quiz.strict = true,
+            Some(("time", false)) => match iter.next().and_then(|(w, _)| duration(w)) {
+                Some(secs) => self.quiz.time = Some(secs),
+                None => bad = true,
+            },
+            Some(("attempts", false)) => match iter.next().and_then(|(w, _)| w.parse().ok()) {
+                Some(n) if n > 0 => self.quiz.attempts = Some(n),
+                _ => bad = true,
+            },
+            Some(("decay", false)) => match iter.next().and_then(|(w, _)| w.parse().ok()) {
+                Some(n @ 0..=100) => self.quiz.decay = Some(n),
+                _ => bad = true,
+            },
+            Some(("mode", false)) => match iter.next() {
+                Some(("quiz", false)) => self.quiz.mode = Mode::Quiz,
+                Some(("exam", false)) => self.quiz.mode = Mode::Exam,
+                _ => bad = true,
+            },
+            Some(("end", false)) => match iter.next() {
+                Some(("section", false)) => self.section = None,
+                _ => bad = true,
+            },
+            Some(("section", false)) => {
+                let Some(s) = self.section else {
+                    self.warn("found section settings outside of a $CATEGORY, ignored them");
+                    return;
+                };
+                while let Some((key, _)) = iter.next() {
+                    match (key, iter.next().and_then(|(w, _)| w.parse::<isize>().ok())) {
+                        ("pass", Some(n)) => self.quiz.sections[s].pass = n,
+                        ("draw", Some(n)) if n > 0 => self.quiz.sections[s].draw = Some(n as usize),
+                        _ => bad = true,
+                    }
+                }
+            }
+            Some(("value", false)) => {
+                let mut iter = words
+                    .iter()
+                    .map(|(w, quoted)| (w.as_str(), *quoted))
+                    .peekable();
+                while let Some((key, _)) = iter.next() {
+                    match key {
+                        "value" => match iter.next().and_then(|(w, _)| w.parse().ok()) {
+                            Some(n) => question.value = n,
+                            None => bad = true,
+                        },
+                        "tags" => {
+                            while let Some(&(tag, true)) = iter.peek() {
+                                let tag = self.string(tag.to_string());
+                                question.tags.push(tag);
+                                iter.next();
+                            }
+                        }
+                        "time" => match iter.next().and_then(|(w, _)| duration(w)) {
+                            Some(secs) => question.time = Some(secs),
+                            None => bad = true,
+                        },
+                        "attempts" => match iter.next().and_then(|(w, _)| w.parse().ok()) {
+                            Some(n) if n > 0 => question.attempts = Some(n),
+                            _ => bad = true,
+                        },
+                        _ => bad = true,
+                    }
+                }
+                self.pending = Some(question);
+            }
+            _ => bad = true,
+        }
+
+        if bad {
+            self.warn(&format!(
+                "couldn't read qqg comment \"{}\", ignored it",
+                line
+            ));
+        }
+    }
+
+    fn category(&mut self, name: &str) {
+        let name = self.string(name.trim().to_string());
+        self.quiz.sections.push(Section {
+            name,
+            ..Default::default()
+        });
+        self.section = Some(self.quiz.sections.len() - 1);
+    }
+
+    fn push(&mut self, text: String, answer: Answer) {
+        let mut question = self.pending.clone().unwrap_or(Question {
+            value: 1,
+            ..Default::default()
+        });
+        question.text = text;
+        question.answer = answer;
+        question.section = self.section;
+        self.quiz.questions.push(question);
+    }
+
+    fn feedback(&mut self, item: &[(char, bool)]) -> usize {
+        match find(item, "#") {
+            Some(at) => {
+                self.warn("answer feedback isn't supported, dropped it");
+                at
+            }
+            None => item.len(),
+        }
+    }
+
+    fn items(&mut self, body: &[(char, bool)]) -> Vec<(char, Option<f64>, Chars)> {
+        let mut items: Vec<(char, Chars)> = Vec::new();
+        for &(c, escaped) in body {
+            match (c, escaped) {
+                ('=' | '~', false) => items.push((c, Vec::new())),
+                _ => match items.last_mut() {
+                    Some((_, item)) => item.push((c, escaped)),
+                    None if c.is_whitespace() => {}
+                    None => items.push(('=', vec![(c, escaped)])),
+                },
+            }
+        }
+
+        items
+            .into_iter()
+            .map(|(marker, mut item)| {
+                let end = self.feedback(&item);
+                item.truncate(end);
+
+                let mut weight = None;
+                let start = item.iter().position(|&(c, _)| !c.is_whitespace());
+                if let Some(start) = start
+                    && item[start] == ('%', false)
+                    && let Some(len) = find(&item[start + 1..], "%")
+                {
+                    weight = text(&item[start + 1..start + 1 + len]).parse::<f64>().ok();
+                    item.drain(..start + len + 2);
+                }
+
+                (marker, weight, item)
+            })
+            .collect()
+    }
+
+    fn question(&mut self, block: &str) {
+        let mut chars = chars(block);
+
+        if chars.starts_with(&[(':', false), (':', false)])
+            && let Some(end) = find(&chars[2..], "::")
+        {
+            chars.drain(..end + 4);
+            self.warn("question titles aren't supported, dropped the title");
+        }
+
+        let start = chars.iter().position(|&(c, _)| !c.is_whitespace());
+        if let Some(start) = start
+            && chars[start] == ('[', false)
+            && let Some(len) = find(&chars[start..], "]")
+        {
+            let format = text(&chars[start + 1..start + len]);
+            if format != "plain" && format != "moodle" {
+                self.warn(&format!(
+                    "{} formatting isn't supported, kept the markup as text",
+                    format
+                ));
+            }
+            chars.drain(..start + len + 1);
+        }
+
+        let (Some(open), Some(close)) = (find(&chars, "{"), find(&chars, "}")) else {
+            self.warn("descriptions without answers aren't supported, skipped it");
+            return;
+        };
+        if close < open {
+            self.warn("found a } before the answers, skipped the question");
+            return;
+        }
+
+        let before = text(&chars[..open]);
+        let after = text(&chars[close + 1..]);
+        let text = self.string(match after.is_empty() {
+            true => before,
+            false => format!("{} _____ {}", before, after),
+        });
+
+        let mut body = chars[open + 1..close].to_vec();
+        if let Some(general) = find(&body, "####") {
+            self.warn("general feedback isn't supported, dropped it");
+            body.truncate(general);
+        }
+
+        let trimmed = self::text(&body);
+        if trimmed.is_empty() {
+            self.warn("essay questions aren't supported, skipped it");
+            return;
+        }
+
+        let head = find(&body, "#").map_or(trimmed.clone(), |at| self::text(&body[..at]));
+        if ["T", "TRUE", "F", "FALSE"].contains(&head.to_uppercase().as_str()) {
+            if find(&body, "#").is_some() {
+                self.warn("answer feedback isn't supported, dropped it");
+            }
+            let truth = head.to_uppercase().starts_with('T');
+            self.push(
+                text,
+                Answer {
+                    answers: vec!["true".to_string(), "false".to_string()],
+                    options: vec![if truth { 1 } else { 2 }],
+                },
+            );
+            return;
+        }
+
+        let first = body.iter().position(|&(c, _)| !c.is_whitespace());
+        if let Some(hash) = first
+            && body[hash] == ('#', false)
+        {
+            let mut answers = Vec::new();
+
+            for (_, weight, item) in self.items(&body[hash + 1..]) {
+                if weight.is_some_and(|w| w < 100.0) {
+                    self.warn(
+                        "partial credit isn't supported, dropped the partially correct answer",
+                    );
+                    continue;
+                }
+
+                let number = self::text(&item);
+                if number.contains("..") {
+                    self.warn("numerical ranges aren't supported, skipped the question");
+                    return;
+                }
+                let (value, tolerance) = number.split_once(':').unwrap_or((&number, "0"));
+                if tolerance.trim().parse::<f64>().is_ok_and(|t| t != 0.0) {
+                    self.warn(
+                        "numerical tolerance isn't supported, only the exact answer is accepted",
+                    );
+                }
+                let answer = self.string(value.trim().to_string());
+                answers.push(answer);
+            }
+
+            if answers.is_empty() {
+                self.warn("found no correct numerical answer, skipped the question");
+                return;
+            }
+            self.push(
+                text,
+                Answer {
+                    answers,
+                    options: Vec::new(),
+                },
+            );
+            return;
+        }
+
+        let items = self.items(&body);
+
+        if items.iter().any(|(_, _, item)| find(item, "->").is_some()) {
+            let mut pairs = Vec::new();
+            for (_, _, item) in &items {
+                match find(item, "->") {
+                    Some(at) => pairs.push((self::text(&item[..at]), self::text(&item[at + 2..]))),
+                    None => {
+                        self.warn("found a matching answer without ->, skipped the question");
+                        return;
+                    }
+                }
+            }
+
+            self.warn(&format!(
+                "matching isn't supported, split it into {} short-answer questions",
+                pairs.iter().filter(|(left, _)| !left.is_empty()).count()
+            ));
+            for (left, right) in pairs {
+                if left.is_empty() {
+                    continue;
+                }
+                let text = self.string(format!("{} {}", text, left));
+                let right = self.string(right);
+                self.push(
+                    text,
+                    Answer {
+                        answers: vec![right],
+                        options: Vec::new(),
+                    },
+                );
+            }
+            return;
+        }
+
+        let mut answer = Answer::default();
+        let choice = items.iter().any(|&(marker, _, _)| marker == '~');
+
+        for (marker, weight, item) in items {
+            let correct = match (marker, weight) {
+                (_, Some(w)) => w > 0.0,
+                ('=', None) => true,
+                _ => false,
+            };
+            if !choice && weight.is_some_and(|w| w < 100.0) {
+                self.warn("partial credit isn't supported, dropped the partially correct answer");
+                continue;
+            }
+
+            let item = self::text(&item);
+            let item = self.string(item);
+            answer.answers.push(item);
+            if choice && correct {
+                answer.options.push(answer.answers.len());
+            }
+        }
+
+        if answer.answers.is_empty() || (choice && answer.options.is_empty()) {
+            self.warn("found no correct answer, skipped the question");
+            return;
+        }
+        self.push(text, answer);
+    }
+}
+
+pub fn parse(file: &str, text: &str) -> (Quiz, Vec<Diagnostic>) {
+    let mut parser = Parser::default();
+    let mut block = String::new();
+    let mut start = 0;
+
+    let lines = text.lines().chain(std::iter::once(""));
+    for (n, line) in lines.enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with("$CATEGORY:") {
+            if !block.trim().is_empty() {
+                parser.line = start;
+                parser.question(&block);
+                parser.pending = None;
+            }
+            block.clear();
+
+            if let Some(name) = trimmed.strip_prefix("$CATEGORY:") {
+                parser.line = n + 1;
+                parser.category(name);
+            }
+        } else if let Some(comment) = trimmed.strip_prefix("//") {
+            if let Some(meta) = comment.trim_start().strip_prefix("qqg ") {
+                parser.line = n + 1;
+                parser.meta(meta.trim());
+            }
+        } else {
+            if block.is_empty() {
+                start = n + 1;
+            }
+            block += line;
+            block.push('\n');
+        }
+    }
+
+    if !parser.titled {
+        let total = parser.quiz.questions.iter().map(|q| q.value).sum::<isize>();
+        parser.quiz.metaline.title = std::path::Path::new(file)
+            .file_stem()
+            .map_or(String::new(), |s| s.to_string_lossy().into_owned());
+        parser.quiz.metaline.pass = (total + 1) / 2;
+        parser.diagnostics.insert(
+            0,
+            Diagnostic {
+                line: 1,
+                message: format!(
+                    "found no qqg title comment, used the file name as title and half of the {} marks as passing marks",
+                    total
+                ),
+            },
+        );
+    }
+
+    (parser.quiz, parser.diagnostics)
+}
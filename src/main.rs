@@ -3,14 +3,29 @@ mod test;
 
 mod args;
 mod ast;
+mod cards;
+mod export;
+mod history;
+mod host;
+mod html;
+mod http;
+mod import;
+mod json;
+mod party;
+mod report;
+mod review;
 mod run;
+mod session;
 mod token;
+mod tui;
 mod utils;
+mod xml;
+mod zip;
 
 fn main() {
     let info = args::parse(std::env::args());
     let text = &std::fs::read_to_string(&info.file)
-        .unwrap_or_else(|e| fatal!("failed to open file: {}", e));
+        .unwrap_or_else(|e| fatal!(utils::Exit::Io; "failed to open file: {}", e));
 
     match info.cmd {
         args::Command::Token => println!("{:#?}", token::ize(&info.file, text)),
@@ -18,6 +33,68 @@ fn main() {
             "{:#?}",
             ast::ify(&mut token::ize(&info.file, text), &info.file)
         ),
-        args::Command::Start => run::start(ast::ify(&mut token::ize(&info.file, text), &info.file)),
+        args::Command::Start => std::process::exit(run::start(
+            ast::ify(&mut token::ize(&info.file, text), &info.file),
+            info.opts,
+            &info.file,
+            text,
+        ) as i32),
+        args::Command::Grade => {
+            let answers = match info.answers.as_deref() {
+                Some(path) if path != "-" => std::fs::read_to_string(path),
+                _ => std::io::read_to_string(std::io::stdin()),
+            }
+            .unwrap_or_else(|e| fatal!(utils::Exit::Io; "failed to read answers: {}", e));
+
+            std::process::exit(run::grade(
+                ast::ify(&mut token::ize(&info.file, text), &info.file),
+                info.opts,
+                &answers,
+            ) as i32)
+        }
+        args::Command::Resume => std::process::exit(session::resume(&info.file, text) as i32),
+        args::Command::Review => std::process::exit(review::review(
+            &ast::ify(&mut token::ize(&info.file, text), &info.file),
+            &info.file,
+            &info.opts,
+        ) as i32),
+        args::Command::Cards => std::process::exit(cards::cards(
+            &ast::ify(&mut token::ize(&info.file, text), &info.file),
+            &info.file,
+            &info.opts,
+        ) as i32),
+        args::Command::Party => std::process::exit(party::party(
+            &ast::ify(&mut token::ize(&info.file, text), &info.file),
+            &info.opts,
+            &info.party,
+        ) as i32),
+        args::Command::Host => std::process::exit(host::host(
+            &ast::ify(&mut token::ize(&info.file, text), &info.file),
+            &info.opts,
+            &info.party,
+        ) as i32),
+        args::Command::Serve => std::process::exit(http::serve(
+            &ast::ify(&mut token::ize(&info.file, text), &info.file),
+            &info.opts,
+            info.port,
+        ) as i32),
+        args::Command::Export(target) => {
+            let out = export::export(
+                &ast::ify(&mut token::ize(&info.file, text), &info.file),
+                &info.opts,
+                &info.export,
+                target,
+            );
+            std::io::Write::write_all(&mut std::io::stdout(), &out)
+                .unwrap_or_else(|e| fatal!(utils::Exit::Io; "failed to write export: {}", e));
+        }
+        args::Command::Import(source) => {
+            std::process::exit(import::import(&info.file, text, source) as i32)
+        }
+        args::Command::Stats => history::stats(
+            &ast::ify(&mut token::ize(&info.file, text), &info.file),
+            &info.file,
+            text,
+        ),
     }
 }
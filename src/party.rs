@@ -0,0 +1,243 @@
+use crate::{
+    ast::Quiz,
+    fatal,
+    run::{self, Input},
+    utils::{Color, Exit, Rng, color, duration},
+};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    #[default]
+    Time,
+    Correct,
+    None,
+}
+
+impl TieBreak {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TieBreak::Time => "time",
+            TieBreak::Correct => "correct",
+            TieBreak::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "time" => Some(TieBreak::Time),
+            "correct" => Some(TieBreak::Correct),
+            "none" => Some(TieBreak::None),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub players: Vec<String>,
+    pub rotate: bool,
+    pub tie_break: TieBreak,
+    pub listen: Option<String>,
+    pub expect: Option<usize>,
+    pub deadline: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    pub score: isize,
+    pub correct: usize,
+    pub time: Duration,
+}
+
+fn order(a: &Player, b: &Player, tie: TieBreak) -> std::cmp::Ordering {
+    b.score.cmp(&a.score).then(match tie {
+        TieBreak::Time => a.time.as_millis().cmp(&b.time.as_millis()),
+        TieBreak::Correct => b.correct.cmp(&a.correct),
+        TieBreak::None => std::cmp::Ordering::Equal,
+    })
+}
+
+pub fn ranking(players: &[Player], tie: TieBreak) -> Vec<(usize, usize)> {
+    let mut sorted = (0..players.len()).collect::<Vec<_>>();
+    sorted.sort_by(|&a, &b| order(&players[a], &players[b], tie));
+
+    let mut ranks = Vec::with_capacity(players.len());
+    for (n, &p) in sorted.iter().enumerate() {
+        let rank = match ranks.last() {
+            Some(&(rank, last)) if order(&players[last], &players[p], tie).is_eq() => rank,
+            _ => n + 1,
+        };
+        ranks.push((rank, p));
+    }
+
+    ranks
+}
+
+pub fn board(players: &[Player], tie: TieBreak, last: bool) {
+    let width = players
+        .iter()
+        .map(|p| p.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for (rank, p) in ranking(players, tie) {
+        let player = &players[p];
+        println!(
+            "{} {} {} {}",
+            color(
+                if last && rank == 1 {
+                    Color::Green
+                } else {
+                    Color::Grey
+                },
+                &format!("{:>3}.", rank)
+            ),
+            color(
+                Color::Yellow,
+                &format!(
+                    "{}{}",
+                    player.name,
+                    " ".repeat(width - player.name.chars().count())
+                )
+            ),
+            color(Color::SuperCyan, &format!("{:>4}", player.score)),
+            color(
+                Color::Grey,
+                &format!(
+                    "{} correct, {}",
+                    player.correct,
+                    duration(player.time.as_secs())
+                )
+            )
+        );
+    }
+}
+
+pub fn party(quiz: &Quiz, opts: &run::Options, setup: &Options) -> Exit {
+    if setup.players.is_empty() {
+        fatal!("{}", color(Color::Red, "expected --players with party"));
+    }
+
+    let seed = opts.seed.unwrap_or_else(Rng::seed);
+    let drawn = run::draw(quiz, opts, &mut Rng::new(seed));
+    if drawn.is_empty() {
+        fatal!(Exit::Quiz; "{}", color(Color::Red, "no questions left to ask"));
+    }
+
+    let mut players = setup
+        .players
+        .iter()
+        .map(|name| Player {
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{}\n{} {}\n{} {}",
+        color(Color::Yellow, &quiz.metaline.title),
+        color(Color::Grey, "by"),
+        color(Color::Yellow, &quiz.metaline.by),
+        color(Color::Grey, "players"),
+        color(Color::Yellow, &setup.players.join(", "))
+    );
+    println!(
+        "{}\n",
+        color(
+            Color::Grey,
+            &format!(
+                "{}, ties broken by {}, type :quit to end the game",
+                if setup.rotate {
+                    "players take turns answering"
+                } else {
+                    "every player answers every question"
+                },
+                setup.tie_break.name()
+            )
+        )
+    );
+
+    let lines = run::lines();
+
+    for (n, &index) in drawn.iter().enumerate() {
+        let question = &quiz.questions[index];
+        let turns = match setup.rotate {
+            true => vec![n % players.len()],
+            false => (0..players.len()).collect(),
+        };
+
+        println!(
+            "{}",
+            color(
+                Color::Grey,
+                &format!("round {}/{} [{}]", n + 1, drawn.len(), question.value)
+            )
+        );
+        run::show(question);
+
+        let mut verdicts = Vec::new();
+        for p in turns {
+            println!(
+                "{}",
+                color(Color::Yellow, &format!("{}'s turn", players[p].name))
+            );
+
+            let started = Instant::now();
+            let input = run::prompt(
+                &lines,
+                question.time.map(|t| started + Duration::from_secs(t)),
+            );
+            players[p].time += started.elapsed();
+
+            let correct = match input {
+                Input::Line(line) if line.trim() != ":quit" => run::judge(question, line.trim()),
+                Input::Timeout => {
+                    println!();
+                    while lines.try_recv().is_ok() {}
+                    false
+                }
+                _ => {
+                    println!("\n{}\n", color(Color::Red, "game ended early"));
+                    board(&players, setup.tie_break, true);
+                    return Exit::Aborted;
+                }
+            };
+
+            if correct {
+                players[p].score += question.value;
+                players[p].correct += 1;
+            }
+            verdicts.push((p, correct));
+        }
+
+        println!();
+        for (p, correct) in verdicts {
+            println!(
+                "{} {}",
+                color(Color::Yellow, &players[p].name),
+                if correct {
+                    color(Color::Green, "correct!")
+                } else {
+                    color(Color::Red, "wrong!")
+                }
+            );
+        }
+        println!(
+            "{}\n",
+            color(
+                Color::Grey,
+                &format!("└─ expected {}", run::expected(question))
+            )
+        );
+
+        board(&players, setup.tie_break, false);
+        println!();
+    }
+
+    println!("{}", color(Color::Yellow, "final leaderboard"));
+    board(&players, setup.tie_break, true);
+
+    Exit::Passed
+}
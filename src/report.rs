@@ -0,0 +1,229 @@
+use crate::{
+    ast::Question,
+    fatal,
+    json::Json,
+    run::{self, Verdict},
+    session::{Session, Summary},
+    utils::{Color, Exit, color},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+pub fn expected(question: &Question) -> Vec<String> {
+    if question.answer.options.is_empty() {
+        question.answer.answers.clone()
+    } else {
+        question
+            .answer
+            .options
+            .iter()
+            .filter_map(|&n| question.answer.answers.get(n - 1).cloned())
+            .collect()
+    }
+}
+
+pub fn json(session: &Session, summary: &Summary) -> String {
+    let quiz = session.quiz;
+
+    let mut fields = vec![
+        ("title".to_string(), quiz.metaline.title.as_str().into()),
+        ("by".to_string(), quiz.metaline.by.as_str().into()),
+        ("seed".to_string(), session.seed.into()),
+        ("exam".to_string(), session.exam.into()),
+        ("score".to_string(), summary.score.into()),
+        ("total".to_string(), summary.total.into()),
+        ("pass".to_string(), summary.pass.into()),
+        ("passed".to_string(), summary.passed.into()),
+        (
+            "time_ms".to_string(),
+            (session.elapsed.as_millis() as u64).into(),
+        ),
+        (
+            "sections".to_string(),
+            Json::Array(
+                quiz.sections
+                    .iter()
+                    .enumerate()
+                    .map(|(s, section)| {
+                        let (score, total) =
+                            run::tally(quiz, &session.records, |q| q.section == Some(s));
+                        Json::Object(vec![
+                            ("name".to_string(), section.name.as_str().into()),
+                            ("score".to_string(), score.into()),
+                            ("total".to_string(), total.into()),
+                            ("pass".to_string(), session.section_pass[s].into()),
+                            ("passed".to_string(), (!summary.failed.contains(&s)).into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "questions".to_string(),
+            Json::Array(
+                session
+                    .records
+                    .iter()
+                    .enumerate()
+                    .map(|(n, record)| {
+                        let question = &quiz.questions[record.index];
+                        Json::Object(vec![
+                            ("number".to_string(), (n + 1).into()),
+                            ("text".to_string(), question.text.as_str().into()),
+                            ("answer".to_string(), record.answer.as_str().into()),
+                            (
+                                "expected".to_string(),
+                                Json::Array(
+                                    expected(question)
+                                        .iter()
+                                        .map(|s| s.as_str().into())
+                                        .collect(),
+                                ),
+                            ),
+                            ("verdict".to_string(), record.verdict.name().into()),
+                            (
+                                "correct".to_string(),
+                                (record.verdict == Verdict::Correct).into(),
+                            ),
+                            ("points".to_string(), record.points.into()),
+                            ("value".to_string(), question.value.into()),
+                            ("attempts".to_string(), record.attempts.into()),
+                            (
+                                "time_ms".to_string(),
+                                (record.time.as_millis() as u64).into(),
+                            ),
+                            (
+                                "tags".to_string(),
+                                Json::Array(
+                                    question.tags.iter().map(|t| t.as_str().into()).collect(),
+                                ),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ];
+
+    if let Some(student) = &session.student {
+        fields.insert(0, ("student".to_string(), student.as_str().into()));
+    }
+
+    if let Some(streak) = session.opts.practice {
+        let (correct, asked) = session
+            .drills
+            .iter()
+            .fold((0, 0), |(c, a), d| (c + d.correct, a + d.asked));
+
+        fields.push((
+            "practice".to_string(),
+            Json::Object(vec![
+                ("streak".to_string(), streak.into()),
+                ("correct".to_string(), correct.into()),
+                ("asked".to_string(), asked.into()),
+                (
+                    "questions".to_string(),
+                    Json::Array(
+                        session
+                            .drills
+                            .iter()
+                            .map(|drill| {
+                                Json::Object(vec![
+                                    (
+                                        "text".to_string(),
+                                        quiz.questions[drill.index].text.as_str().into(),
+                                    ),
+                                    ("correct".to_string(), drill.correct.into()),
+                                    ("asked".to_string(), drill.asked.into()),
+                                    ("mastered".to_string(), (drill.streak >= streak).into()),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                ),
+            ]),
+        ));
+    }
+
+    Json::Object(fields).to_string() + "\n"
+}
+
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn csv(session: &Session, summary: &Summary) -> String {
+    let quiz = session.quiz;
+    let student = session.student.as_deref().map(|s| field(s) + ",");
+    let mut out = format!(
+        "{}title,by,seed,number,question,answer,expected,verdict,points,value,attempts,time_ms,score,total,pass,passed\n",
+        if student.is_some() { "student," } else { "" }
+    );
+
+    for (n, record) in session.records.iter().enumerate() {
+        let question = &quiz.questions[record.index];
+        out += student.as_deref().unwrap_or_default();
+        out += &[
+            field(&quiz.metaline.title),
+            field(&quiz.metaline.by),
+            session.seed.to_string(),
+            (n + 1).to_string(),
+            field(&question.text),
+            field(&record.answer),
+            field(&expected(question).join(" | ")),
+            record.verdict.name().to_string(),
+            record.points.to_string(),
+            question.value.to_string(),
+            record.attempts.to_string(),
+            record.time.as_millis().to_string(),
+            summary.score.to_string(),
+            summary.total.to_string(),
+            summary.pass.to_string(),
+            summary.passed.to_string(),
+        ]
+        .join(",");
+        out.push('\n');
+    }
+
+    out
+}
+
+pub fn write(session: &Session, summary: &Summary, format: Format, path: &str) {
+    let out = match format {
+        Format::Json => json(session, summary),
+        Format::Csv => csv(session, summary),
+    };
+
+    std::fs::write(path, out).unwrap_or_else(|e| {
+        fatal!(
+            Exit::Io;
+            "{}",
+            color(Color::Red, &format!("failed to write report: {}", e))
+        )
+    });
+}
@@ -0,0 +1,318 @@
+use crate::{
+    ast::{Question, Quiz},
+    fatal,
+    run::{self, Input, Options},
+    utils::{Color, Exit, color, data_dir, date, hash, now},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub quiz: u64,
+    pub id: u64,
+    pub text: u64,
+    pub ease: u32,
+    pub interval: u64,
+    pub reps: u32,
+    pub due: u64,
+}
+
+impl Card {
+    pub fn new(question: &Question, quiz: u64, today: u64) -> Self {
+        Self {
+            quiz,
+            id: id(question),
+            text: hash(&question.text),
+            ease: 250,
+            interval: 0,
+            reps: 0,
+            due: today,
+        }
+    }
+
+    pub fn schedule(&mut self, quality: u32, today: u64) {
+        let quality = quality.min(5);
+
+        if quality >= 3 {
+            self.interval = match self.reps {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval * self.ease as u64).div_ceil(100),
+            };
+            self.reps += 1;
+        } else {
+            self.interval = 1;
+            self.reps = 0;
+        }
+
+        let miss = 5 - quality;
+        self.ease = (self.ease + 10)
+            .saturating_sub(miss * (8 + miss * 2))
+            .max(130);
+        self.due = today + self.interval;
+    }
+
+    pub fn line(&self) -> String {
+        format!(
+            "{:016x}\t{:016x}\t{:016x}\t{}\t{}\t{}\t{}",
+            self.quiz, self.id, self.text, self.ease, self.interval, self.reps, self.due
+        )
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t').collect::<Vec<_>>();
+        // cards saved before they were keyed by quiz have no quiz field
+        if fields.len() == 6 {
+            fields.insert(0, "0");
+        }
+
+        let mut fields = fields.into_iter();
+        let card = Self {
+            quiz: u64::from_str_radix(fields.next()?, 16).ok()?,
+            id: u64::from_str_radix(fields.next()?, 16).ok()?,
+            text: u64::from_str_radix(fields.next()?, 16).ok()?,
+            ease: fields.next()?.parse().ok()?,
+            interval: fields.next()?.parse().ok()?,
+            reps: fields.next()?.parse().ok()?,
+            due: fields.next()?.parse().ok()?,
+        };
+
+        fields.next().is_none().then_some(card)
+    }
+}
+
+pub fn id(question: &Question) -> u64 {
+    let mut text = question.text.clone();
+
+    for answer in &question.answer.answers {
+        text += "\n";
+        text += answer;
+    }
+
+    for option in &question.answer.options {
+        text += &format!("\n#{}", option);
+    }
+
+    hash(&text)
+}
+
+pub fn key(file: &str) -> u64 {
+    hash(
+        &std::fs::canonicalize(file)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| file.to_string()),
+    )
+}
+
+pub fn today() -> u64 {
+    now() / 86400
+}
+
+pub fn day(day: u64) -> String {
+    date(day * 86400)[..10].to_string()
+}
+
+fn dir() -> std::path::PathBuf {
+    data_dir().unwrap_or_else(|| {
+        fatal!(
+            Exit::Io;
+            "{}",
+            color(
+                Color::Red,
+                "no data directory found, set XDG_DATA_HOME or HOME to keep cards"
+            )
+        )
+    })
+}
+
+pub fn load() -> Vec<Card> {
+    match std::fs::read_to_string(dir().join("cards")) {
+        Ok(text) => text.lines().filter_map(Card::parse).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => fatal!(
+            Exit::Io;
+            "{}",
+            color(Color::Red, &format!("failed to read cards: {}", e))
+        ),
+    }
+}
+
+pub fn save(cards: &[Card]) {
+    let dir = dir();
+    let out = cards.iter().map(|c| c.line() + "\n").collect::<String>();
+
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(dir.join("cards"), out))
+        .unwrap_or_else(|e| {
+            fatal!(
+                Exit::Io;
+                "{}",
+                color(Color::Red, &format!("failed to save cards: {}", e))
+            )
+        });
+}
+
+pub fn deck(
+    quiz: &Quiz,
+    key: u64,
+    opts: &Options,
+    cards: &mut Vec<Card>,
+    today: u64,
+) -> (Vec<(usize, usize)>, usize) {
+    let kept = (0..quiz.questions.len())
+        .filter(|&index| opts.keeps(&quiz.questions[index]))
+        .map(|index| (index, Card::new(&quiz.questions[index], key, today)))
+        .collect::<Vec<_>>();
+    let mut found = vec![None; kept.len()];
+    let mut claimed = vec![false; cards.len()];
+
+    for (n, (_, card)) in kept.iter().enumerate() {
+        if let Some(pos) = (0..cards.len()).find(|&pos| {
+            !claimed[pos]
+                && cards[pos].id == card.id
+                && (cards[pos].quiz == key || cards[pos].quiz == 0)
+        }) {
+            cards[pos].quiz = key;
+            claimed[pos] = true;
+            found[n] = Some(pos);
+        }
+    }
+
+    let mut deck = Vec::new();
+    let mut edited = 0;
+
+    for ((index, card), found) in kept.into_iter().zip(found) {
+        let pos = match found {
+            Some(pos) => pos,
+            None => match (0..claimed.len()).find(|&pos| {
+                !claimed[pos] && cards[pos].quiz == key && cards[pos].text == card.text
+            }) {
+                Some(pos) => {
+                    cards[pos] = card;
+                    claimed[pos] = true;
+                    edited += 1;
+                    pos
+                }
+                None => {
+                    cards.push(card);
+                    cards.len() - 1
+                }
+            },
+        };
+
+        deck.push((index, pos));
+    }
+
+    (deck, edited)
+}
+
+pub fn due(cards: &[Card], deck: &[(usize, usize)], today: u64) -> Vec<(usize, usize)> {
+    let mut due = deck
+        .iter()
+        .copied()
+        .filter(|&(_, pos)| cards[pos].due <= today)
+        .collect::<Vec<_>>();
+
+    due.sort_by_key(|&(index, pos)| (cards[pos].due, index));
+    due
+}
+
+pub fn upcoming(cards: &[Card], deck: &[(usize, usize)]) {
+    if let Some(due) = deck.iter().map(|&(_, pos)| cards[pos].due).min() {
+        println!(
+            "{} {}",
+            color(Color::Grey, "next review on"),
+            color(Color::Yellow, &day(due))
+        );
+    }
+}
+
+pub fn review(quiz: &Quiz, file: &str, opts: &Options) -> Exit {
+    let today = today();
+    let mut cards = load();
+    let (deck, edited) = deck(quiz, key(file), opts, &mut cards, today);
+    let due = due(&cards, &deck, today);
+
+    println!(
+        "{}\n{} {}\n",
+        color(Color::Yellow, &quiz.metaline.title),
+        color(Color::Grey, "by"),
+        color(Color::Yellow, &quiz.metaline.by)
+    );
+
+    if edited > 0 {
+        println!(
+            "{}",
+            color(
+                Color::Grey,
+                &format!("{} edited cards start over from scratch", edited)
+            )
+        );
+    }
+
+    println!(
+        "{}\n",
+        color(
+            Color::Grey,
+            &format!(
+                "{} of {} cards due, type :quit to stop",
+                due.len(),
+                deck.len()
+            )
+        )
+    );
+
+    let lines = run::lines();
+    let mut recalled = 0;
+
+    for (n, &(index, pos)) in due.iter().enumerate() {
+        let question = &quiz.questions[index];
+        run::show(question);
+
+        let answer = match run::prompt(&lines, None) {
+            Input::Line(line) if line.trim() != ":quit" => line.trim().to_string(),
+            _ => {
+                save(&cards);
+                println!(
+                    "\n{} {}",
+                    color(Color::Red, "review stopped"),
+                    color(Color::Grey, &format!("after {} of {} cards", n, due.len()))
+                );
+                return Exit::Aborted;
+            }
+        };
+
+        if run::judge(question, &answer) {
+            recalled += 1;
+            cards[pos].schedule(4, today);
+            println!(
+                "{} {}\n",
+                color(Color::Green, "correct answer!"),
+                color(Color::Grey, &format!("[due again {}]", day(cards[pos].due)))
+            );
+        } else {
+            cards[pos].schedule(1, today);
+            println!(
+                "{} \n",
+                color(
+                    Color::Red,
+                    &format!("wrong answer!\n└─ expected {}", run::expected(question))
+                )
+            );
+        }
+
+        save(&cards);
+    }
+
+    save(&cards);
+
+    if !due.is_empty() {
+        println!(
+            "{} {}",
+            color(Color::Grey, "recalled"),
+            color(Color::SuperCyan, &format!("{}/{}", recalled, due.len()))
+        );
+    }
+    upcoming(&cards, &deck);
+
+    Exit::Passed
+}
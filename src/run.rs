@@ -1,125 +1,394 @@
-use crate::utils::{Color, color};
-use crate::{ast::Quiz, fatal};
+use crate::utils::{Color, Exit, Rng, color, duration};
+use crate::{
+    ast::{Question, Quiz},
+    fatal,
+    report::Format,
+    session::Session,
+};
 use std::io::{Write, stdin, stdout};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::{Duration, Instant};
 
-pub fn start(quiz: Quiz) {
-    println!(
-        "{}\n{} {}\n{} {}\n",
-        color(Color::Yellow, &quiz.metaline.title),
-        color(Color::Grey, "by"),
-        color(Color::Yellow, &quiz.metaline.by),
-        color(Color::Grey, "passing marks"),
-        color(Color::Yellow, &quiz.metaline.pass.to_string())
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub seed: Option<u64>,
+    pub tags: Vec<String>,
+    pub exclude: Vec<String>,
+    pub exam: bool,
+    pub session: Option<String>,
+    pub report: Option<(Format, String)>,
+    pub no_history: bool,
+    pub practice: Option<usize>,
+    pub schedule: bool,
+    pub tui: bool,
+    pub hide_score: bool,
+}
+
+impl Options {
+    pub fn keeps(&self, question: &Question) -> bool {
+        (self.tags.is_empty() || question.tags.iter().any(|t| self.tags.contains(t)))
+            && !question.tags.iter().any(|t| self.exclude.contains(t))
+    }
+}
+
+fn sample(mut indices: Vec<usize>, count: Option<usize>, rng: &mut Rng) -> Vec<usize> {
+    let count = count.unwrap_or(indices.len()).min(indices.len());
+
+    for i in 0..count {
+        let j = i + rng.below(indices.len() - i);
+        indices.swap(i, j);
+    }
+
+    indices.truncate(count);
+    indices
+}
+
+pub fn pool(quiz: &Quiz, question: &Question) -> Option<usize> {
+    if question
+        .section
+        .is_some_and(|s| quiz.sections[s].draw.is_some())
+    {
+        return None;
+    }
+
+    quiz.pools
+        .iter()
+        .position(|p| question.tags.contains(&p.tag))
+}
+
+pub fn draw(quiz: &Quiz, opts: &Options, rng: &mut Rng) -> Vec<usize> {
+    let kept = (0..quiz.questions.len())
+        .filter(|&i| opts.keeps(&quiz.questions[i]))
+        .collect::<Vec<_>>();
+    let sectioned = |i: usize| {
+        quiz.questions[i]
+            .section
+            .is_some_and(|s| quiz.sections[s].draw.is_some())
+    };
+    let tagged = |i: usize| pool(quiz, &quiz.questions[i]);
+
+    let mut drawn = sample(
+        kept.iter()
+            .copied()
+            .filter(|&i| !sectioned(i) && tagged(i).is_none())
+            .collect(),
+        quiz.draw,
+        rng,
     );
 
-    let mut total = 0;
-    let mut score = 0;
+    for (index, section) in quiz.sections.iter().enumerate() {
+        if section.draw.is_some() {
+            drawn.extend(sample(
+                kept.iter()
+                    .copied()
+                    .filter(|&i| quiz.questions[i].section == Some(index))
+                    .collect(),
+                section.draw,
+                rng,
+            ));
+        }
+    }
 
-    for question in quiz.questions {
-        total += question.value;
-        println!(
-            "{} {}",
-            color(Color::SuperCyan, &("┌ ".to_string() + &question.text)),
-            color(Color::Grey, &format!("[{}]", question.value))
-        );
+    for (index, pool) in quiz.pools.iter().enumerate() {
+        drawn.extend(sample(
+            kept.iter()
+                .copied()
+                .filter(|&i| tagged(i) == Some(index))
+                .collect(),
+            Some(pool.draw),
+            rng,
+        ));
+    }
 
-        let is_option = !question.answer.options.is_empty();
+    drawn.sort_unstable();
+    drawn
+}
 
-        if is_option {
-            for (index, answer) in question.answer.answers.iter().enumerate() {
-                println!(
-                    "{} {}",
-                    color(Color::Grey, &(index + 1).to_string()),
-                    color(Color::Yellow, answer)
-                )
+pub fn marks(quiz: &Quiz, indices: impl Iterator<Item = usize>, section: Option<usize>) -> isize {
+    indices
+        .map(|i| &quiz.questions[i])
+        .filter(|q| section.is_none() || q.section == section)
+        .map(|q| q.value)
+        .sum()
+}
+
+pub fn judge(question: &Question, answer: &str) -> bool {
+    if question.answer.options.is_empty() {
+        question.answer.answers.iter().any(|a| a == answer)
+    } else {
+        let picked = answer
+            .split([',', ' '])
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>();
+
+        match picked {
+            Ok(picked) if picked.len() == 1 => question.answer.options.contains(&picked[0]),
+            Ok(mut picked) => {
+                let mut options = question.answer.options.clone();
+                picked.sort_unstable();
+                picked.dedup();
+                options.sort_unstable();
+                options.dedup();
+                picked == options
             }
+            Err(_) => false,
+        }
+    }
+}
+
+pub fn expected(question: &Question) -> String {
+    if question.answer.options.is_empty() {
+        question
+            .answer
+            .answers
+            .iter()
+            .map(|s| format!("\"{}\"", s))
+            .collect::<Vec<_>>()
+            .join(" or ")
+    } else {
+        "options ".to_string()
+            + &question
+                .answer
+                .options
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" or ")
+    }
+}
+
+pub fn award(value: isize, attempts: usize, decay: Option<isize>) -> isize {
+    let decay = decay.unwrap_or(50);
+    (1..attempts).fold(value, |value, _| {
+        let kept = value * decay;
+        kept / 100 + (kept % 100 > 0) as isize
+    })
+}
+
+pub fn required(pass: isize, drawn: isize, total: isize) -> isize {
+    if drawn == total || total <= 0 {
+        return pass;
+    }
+
+    (pass * drawn + total - 1).div_euclid(total)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    Timeout,
+    Skipped,
+}
+
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong => "wrong",
+            Verdict::Timeout => "timeout",
+            Verdict::Skipped => "skipped",
         }
+    }
 
-        print!("{} ", color(Color::SuperCyan, "└──"));
-        stdout().flush().unwrap_or_else(|e| {
-            fatal!(
-                "{}",
-                color(Color::Red, &format!("failed to flush stdout: {}", e))
-            )
-        });
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "correct" => Some(Verdict::Correct),
+            "wrong" => Some(Verdict::Wrong),
+            "timeout" => Some(Verdict::Timeout),
+            "skipped" => Some(Verdict::Skipped),
+            _ => None,
+        }
+    }
+}
 
-        let mut answer = String::new();
-        stdin().read_line(&mut answer).unwrap_or_else(|e| {
-            fatal!(
-                "{}",
-                color(Color::Red, &format!("failed to read stdin: {}", e))
-            )
-        });
-        answer = answer.trim().to_string();
-
-        if is_option {
-            if question.answer.options.contains(
-                &answer
-                    .parse::<usize>()
-                    .unwrap_or_else(|e| fatal!("failed to parse number: {}", e)),
-            ) {
-                println!("{}\n", color(Color::Green, "correct answer!"));
-                score += question.value;
-            } else {
-                println!(
-                    "{} \n",
-                    color(
-                        Color::Red,
-                        &format!(
-                            "wrong answer!\n└─ expected options {}",
-                            question
-                                .answer
-                                .options
-                                .iter()
-                                .map(|n| n.to_string())
-                                .collect::<Vec<_>>()
-                                .join(" or ")
-                        )
-                    )
-                );
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub index: usize,
+    pub answer: String,
+    pub verdict: Verdict,
+    pub points: isize,
+    pub attempts: usize,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drill {
+    pub index: usize,
+    pub asked: usize,
+    pub correct: usize,
+    pub streak: usize,
+}
+
+pub fn tally(
+    quiz: &Quiz,
+    records: &[Record],
+    filter: impl Fn(&Question) -> bool,
+) -> (isize, isize) {
+    records
+        .iter()
+        .filter(|r| filter(&quiz.questions[r.index]))
+        .fold((0, 0), |(score, total), r| {
+            (score + r.points, total + quiz.questions[r.index].value)
+        })
+}
+
+pub fn lines() -> Receiver<String> {
+    let (tx, rx) = channel();
+
+    std::thread::spawn(move || {
+        for line in stdin().lines() {
+            let line = line.unwrap_or_else(|e| {
+                fatal!(
+                    Exit::Io;
+                    "{}",
+                    color(Color::Red, &format!("failed to read stdin: {}", e))
+                )
+            });
+
+            if tx.send(line).is_err() {
+                break;
             }
-        } else if question.answer.answers.contains(&answer) {
-            println!("{}\n", color(Color::Green, "correct answer!"));
-            score += question.value;
-        } else {
+        }
+    });
+
+    rx
+}
+
+pub fn show(question: &Question) {
+    println!(
+        "{}",
+        color(Color::SuperCyan, &("┌ ".to_string() + &question.text))
+    );
+
+    if !question.answer.options.is_empty() {
+        for (index, answer) in question.answer.answers.iter().enumerate() {
             println!(
-                "{} \n",
-                color(
-                    Color::Red,
-                    &format!(
-                        "wrong answer!\n└─ expected {}",
-                        question
-                            .answer
-                            .answers
-                            .iter()
-                            .map(|s| format!("\"{}\"", s))
-                            .collect::<Vec<_>>()
-                            .join(" or ")
+                "{} {}",
+                color(Color::Grey, &(index + 1).to_string()),
+                color(Color::Yellow, answer)
+            )
+        }
+    }
+}
+
+pub enum Input {
+    Line(String),
+    Timeout,
+    Closed,
+}
+
+pub fn prompt(lines: &Receiver<String>, deadline: Option<Instant>) -> Input {
+    match deadline {
+        Some(deadline) => print!(
+            "{} {} ",
+            color(Color::SuperCyan, "└──"),
+            color(
+                Color::Grey,
+                &format!(
+                    "[{} left]",
+                    duration(
+                        deadline
+                            .saturating_duration_since(Instant::now())
+                            .as_secs_f64()
+                            .ceil() as u64
                     )
                 )
-            );
-        };
+            )
+        ),
+        None => print!("{} ", color(Color::SuperCyan, "└──")),
     }
 
-    let pass = score >= quiz.metaline.pass;
+    stdout().flush().unwrap_or_else(|e| {
+        fatal!(
+            Exit::Io;
+            "{}",
+            color(Color::Red, &format!("failed to flush stdout: {}", e))
+        )
+    });
 
-    println!(
-        "{} {} {} {}",
-        color(Color::Yellow, "you scored"),
-        color(
-            if pass { Color::Green } else { Color::Red },
-            &score.to_string()
-        ),
-        color(Color::Yellow, "out of"),
-        color(Color::SuperCyan, &total.to_string())
-    );
+    match deadline {
+        Some(deadline) => {
+            match lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => Input::Line(line),
+                Err(RecvTimeoutError::Timeout) => Input::Timeout,
+                Err(RecvTimeoutError::Disconnected) => Input::Closed,
+            }
+        }
+        None => lines.recv().map_or(Input::Closed, Input::Line),
+    }
+}
 
-    println!(
-        "{}",
-        if pass {
-            color(Color::Green, "you passed!")
-        } else {
-            color(Color::Red, "you failed!")
+pub fn answers(text: &str, count: usize) -> Vec<Option<String>> {
+    let lines = text.lines().map(str::trim).collect::<Vec<_>>();
+    let indexed = lines
+        .iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            l.split_once(':')
+                .and_then(|(n, a)| Some((n.trim().parse::<usize>().ok()?, a.trim())))
+        })
+        .collect::<Option<Vec<_>>>();
+
+    let mut answers = vec![None; count];
+
+    match indexed {
+        Some(indexed) if !indexed.is_empty() => {
+            for (n, answer) in indexed {
+                if (1..=count).contains(&n) {
+                    answers[n - 1] = Some(answer.to_string());
+                } else {
+                    fatal!(
+                        Exit::Quiz;
+                        "{}",
+                        color(
+                            Color::Red,
+                            &format!("encountered answer for question {}; expected 1 to {}", n, count)
+                        )
+                    );
+                }
+            }
         }
-    );
+        _ => {
+            for (slot, line) in answers.iter_mut().zip(lines) {
+                if !line.is_empty() {
+                    *slot = Some(line.to_string());
+                }
+            }
+        }
+    }
+
+    answers
+}
+
+pub fn draws(quiz: &Quiz) -> bool {
+    quiz.draw.is_some() || !quiz.pools.is_empty() || quiz.sections.iter().any(|s| s.draw.is_some())
+}
+
+pub fn grade(quiz: Quiz, opts: Options, text: &str) -> Exit {
+    if draws(&quiz) && opts.seed.is_none() {
+        fatal!(
+            Exit::Usage;
+            "{}",
+            color(
+                Color::Red,
+                "this quiz draws its questions, pass the --seed of the run the answers are for"
+            )
+        );
+    }
+
+    let mut session = Session::new(&quiz, &opts);
+    session.header();
+    println!();
+
+    session.grade(answers(text, session.records.len()));
+    session.report()
+}
+
+pub fn start(quiz: Quiz, opts: Options, file: &str, text: &str) -> Exit {
+    let mut session = Session::new(&quiz, &opts);
+    session.source(file, text);
+
+    session.complete()
 }
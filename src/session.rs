@@ -0,0 +1,1152 @@
+use crate::utils::{Color, Exit, Rng, color, duration, hash};
+use crate::{
+    ast::{self, Mode, Quiz},
+    fatal, history,
+    report::{self, Format},
+    run::{self, Drill, Input, Options, Record, Verdict},
+    token, tui,
+};
+use std::collections::VecDeque;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+pub struct Summary {
+    pub score: isize,
+    pub total: isize,
+    pub pass: isize,
+    pub passed: bool,
+    pub failed: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attempt {
+    Locked,
+    Retry(usize),
+    Resolved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Ask(usize),
+    Submit,
+    Done,
+    Aborted,
+}
+
+fn subtotal(name: &str, (score, total): (isize, isize), pass: isize) {
+    let passed = score >= pass;
+
+    println!(
+        "{} {} {} {} {} {}\n",
+        color(Color::Grey, &format!("└ {}:", name)),
+        color(
+            if passed { Color::Green } else { Color::Red },
+            &score.to_string()
+        ),
+        color(Color::Grey, "out of"),
+        color(Color::SuperCyan, &total.to_string()),
+        color(Color::Grey, "passing marks"),
+        color(Color::Yellow, &pass.to_string())
+    );
+}
+
+fn cell(text: &str, width: usize) -> String {
+    let mut out = text.chars().take(width).collect::<String>();
+    if text.chars().count() > width {
+        out.pop();
+        out.push('…');
+    }
+
+    let len = out.chars().count();
+    out + &" ".repeat(width - len)
+}
+
+fn review(quiz: &Quiz, records: &[Record]) {
+    let rows = records
+        .iter()
+        .enumerate()
+        .map(|(n, r)| {
+            let question = &quiz.questions[r.index];
+            (
+                (n + 1).to_string(),
+                question.text.as_str(),
+                match r.verdict {
+                    Verdict::Timeout => "(timed out)".to_string(),
+                    Verdict::Skipped => "(skipped)".to_string(),
+                    _ => r.answer.to_string(),
+                },
+                run::expected(question),
+                format!("{}/{}", r.points, question.value),
+                r.verdict == Verdict::Correct,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let width = |header: &str, f: &dyn Fn(usize) -> usize| {
+        (0..rows.len())
+            .map(f)
+            .max()
+            .unwrap_or(0)
+            .max(header.len())
+            .min(40)
+    };
+    let widths = [
+        width("#", &|i| rows[i].0.chars().count()),
+        width("question", &|i| rows[i].1.chars().count()),
+        width("answer", &|i| rows[i].2.chars().count()),
+        width("expected", &|i| rows[i].3.chars().count()),
+    ];
+
+    println!(
+        "{}",
+        color(
+            Color::Grey,
+            &format!(
+                "{} {} {} {} points",
+                cell("#", widths[0]),
+                cell("question", widths[1]),
+                cell("answer", widths[2]),
+                cell("expected", widths[3])
+            )
+        )
+    );
+
+    for (index, text, answer, expected, points, correct) in &rows {
+        println!(
+            "{} {} {} {} {}",
+            color(Color::Grey, &cell(index, widths[0])),
+            color(Color::SuperCyan, &cell(text, widths[1])),
+            color(Color::Yellow, &cell(answer, widths[2])),
+            color(Color::Yellow, &cell(expected, widths[3])),
+            color(if *correct { Color::Green } else { Color::Red }, points)
+        );
+    }
+    println!();
+}
+
+pub struct Session<'a> {
+    pub quiz: &'a Quiz,
+    pub opts: Options,
+    pub exam: bool,
+    pub seed: u64,
+    pub records: Vec<Record>,
+    pub pass: isize,
+    pub section_pass: Vec<isize>,
+    pub elapsed: Duration,
+    pub file: String,
+    pub hash: u64,
+    pub student: Option<String>,
+    pub drills: Vec<Drill>,
+    shown: Vec<bool>,
+    heading: Option<usize>,
+    asked: Option<usize>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(quiz: &'a Quiz, opts: &Options) -> Self {
+        let seed = opts.seed.unwrap_or_else(Rng::seed);
+        let drawn = run::draw(quiz, opts, &mut Rng::new(seed));
+
+        if drawn.is_empty() && !quiz.questions.is_empty() {
+            fatal!(Exit::Quiz; "{}", color(Color::Red, "no questions left to ask"));
+        }
+
+        let everything = 0..quiz.questions.len();
+        let pass = run::required(
+            quiz.metaline.pass,
+            run::marks(quiz, drawn.iter().copied(), None),
+            run::marks(quiz, everything.clone(), None),
+        );
+        let section_pass = (0..quiz.sections.len())
+            .map(|s| {
+                run::required(
+                    quiz.sections[s].pass,
+                    run::marks(quiz, drawn.iter().copied(), Some(s)),
+                    run::marks(quiz, everything.clone(), Some(s)),
+                )
+            })
+            .collect();
+
+        Self {
+            quiz,
+            opts: Options {
+                seed: Some(seed),
+                ..opts.clone()
+            },
+            exam: opts.exam || quiz.mode == Mode::Exam,
+            seed,
+            records: drawn
+                .into_iter()
+                .map(|index| Record {
+                    index,
+                    answer: String::new(),
+                    verdict: Verdict::Skipped,
+                    points: 0,
+                    attempts: 0,
+                    time: Duration::ZERO,
+                })
+                .collect(),
+            pass,
+            section_pass,
+            elapsed: Duration::ZERO,
+            file: String::new(),
+            hash: 0,
+            student: None,
+            drills: Vec::new(),
+            shown: vec![false; quiz.sections.len()],
+            heading: None,
+            asked: None,
+        }
+    }
+
+    pub fn source(&mut self, file: &str, text: &str) {
+        self.file = std::fs::canonicalize(file)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| file.to_string());
+        self.hash = hash(text);
+    }
+
+    pub fn header(&self) {
+        let quiz = self.quiz;
+
+        println!(
+            "{}\n{} {}\n{} {}",
+            color(Color::Yellow, &quiz.metaline.title),
+            color(Color::Grey, "by"),
+            color(Color::Yellow, &quiz.metaline.by),
+            color(Color::Grey, "passing marks"),
+            color(Color::Yellow, &self.pass.to_string())
+        );
+
+        if let Some(time) = quiz.time {
+            println!(
+                "{} {}",
+                color(Color::Grey, "time limit"),
+                color(Color::Yellow, &duration(time))
+            );
+        }
+
+        if quiz.strict && !quiz.sections.is_empty() {
+            println!("{}", color(Color::Grey, "every section must be passed"));
+        }
+
+        if self.exam {
+            println!(
+                "{}",
+                color(Color::Grey, "exam mode: answers are reviewed at the end")
+            );
+        }
+
+        if self.records.len() < quiz.questions.len() {
+            println!(
+                "{} {} {} {} {}",
+                color(Color::Grey, "drawing"),
+                color(Color::Yellow, &self.records.len().to_string()),
+                color(Color::Grey, "of"),
+                color(Color::Yellow, &quiz.questions.len().to_string()),
+                color(Color::Grey, &format!("questions (seed {})", self.seed))
+            );
+        }
+    }
+
+    pub fn intro(&self) {
+        self.header();
+        println!(
+            "{}\n",
+            color(
+                Color::Grey,
+                "type :skip, :back, :goto <n>, :list, :submit or :quit at the prompt"
+            )
+        );
+    }
+
+    pub fn locked(&self, pos: usize) -> bool {
+        match self.records[pos].verdict {
+            Verdict::Skipped => false,
+            Verdict::Timeout => true,
+            _ => !self.exam,
+        }
+    }
+
+    pub fn next(&self, after: usize) -> State {
+        (after.wrapping_add(1)..self.records.len())
+            .find(|&p| self.records[p].verdict == Verdict::Skipped)
+            .map_or(State::Submit, State::Ask)
+    }
+
+    pub fn unanswered(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|r| r.verdict == Verdict::Skipped)
+            .count()
+    }
+
+    pub fn deadline(&self, pos: Option<usize>) -> Option<Instant> {
+        let quiz = self
+            .quiz
+            .time
+            .map(|t| Duration::from_secs(t).saturating_sub(self.elapsed));
+        let question = pos.and_then(|p| {
+            self.quiz.questions[self.records[p].index]
+                .time
+                .map(|t| Duration::from_secs(t).saturating_sub(self.records[p].time))
+        });
+
+        match (quiz, question) {
+            (Some(a), Some(b)) => Some(Instant::now() + a.min(b)),
+            (a, b) => a.or(b).map(|d| Instant::now() + d),
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.quiz
+            .time
+            .is_some_and(|t| self.elapsed >= Duration::from_secs(t))
+    }
+
+    pub fn lapse(&mut self) {
+        for record in &mut self.records {
+            if record.verdict == Verdict::Skipped {
+                record.verdict = Verdict::Timeout;
+            }
+        }
+    }
+
+    fn expire(&mut self) -> State {
+        println!("\n{}\n", color(Color::Red, "time is up!"));
+        self.lapse();
+        State::Done
+    }
+
+    fn read(&mut self, lines: &Receiver<String>, pos: Option<usize>) -> Input {
+        let started = Instant::now();
+        let input = run::prompt(lines, self.deadline(pos));
+        let spent = started.elapsed();
+
+        self.elapsed += spent;
+        if let Some(p) = pos {
+            self.records[p].time += spent;
+        }
+
+        input
+    }
+
+    fn show(&mut self, pos: usize) {
+        let record = &self.records[pos];
+        let question = &self.quiz.questions[record.index];
+
+        if question.section != self.heading {
+            if let Some(s) = question.section {
+                println!(
+                    "{}\n",
+                    color(Color::Yellow, &format!("§ {}", self.quiz.sections[s].name))
+                );
+            }
+            self.heading = question.section;
+        }
+
+        println!("{}", color(Color::Grey, &self.progress(pos)));
+        println!(
+            "{} {}",
+            color(Color::SuperCyan, &("┌ ".to_string() + &question.text)),
+            color(Color::Grey, &format!("[{}]", question.value))
+        );
+
+        if !question.answer.options.is_empty() {
+            for (index, answer) in question.answer.answers.iter().enumerate() {
+                println!(
+                    "{} {}",
+                    color(Color::Grey, &(index + 1).to_string()),
+                    color(Color::Yellow, answer)
+                )
+            }
+        }
+
+        match record.verdict {
+            Verdict::Skipped => {}
+            Verdict::Timeout => println!("{}", color(Color::Grey, "└─ timed out")),
+            _ if self.exam => println!(
+                "{}",
+                color(
+                    Color::Grey,
+                    &format!("└─ your answer \"{}\"", record.answer)
+                )
+            ),
+            _ => println!(
+                "{}",
+                color(
+                    Color::Grey,
+                    &format!("└─ already answered \"{}\"", record.answer)
+                )
+            ),
+        }
+
+        self.asked = Some(pos);
+    }
+
+    pub fn progress(&self, pos: usize) -> String {
+        let mut out = format!("question {}/{}", pos + 1, self.records.len());
+
+        if !self.exam && !self.opts.hide_score {
+            let (score, _) = run::tally(self.quiz, &self.records, |_| true);
+            let open = self
+                .records
+                .iter()
+                .filter(|r| r.verdict == Verdict::Skipped)
+                .map(|r| self.quiz.questions[r.index].value)
+                .sum::<isize>();
+            out += &format!(" · score {} · {} still possible", score, open);
+        }
+
+        out
+    }
+
+    fn list(&self) {
+        for (pos, record) in self.records.iter().enumerate() {
+            let status = match record.verdict {
+                Verdict::Skipped => color(Color::Grey, "unanswered"),
+                Verdict::Timeout => color(Color::Red, "timed out"),
+                _ if self.exam => color(Color::Yellow, "answered"),
+                Verdict::Correct => color(Color::Green, "correct"),
+                Verdict::Wrong => color(Color::Red, "wrong"),
+            };
+
+            println!(
+                "{} {} {}",
+                color(Color::Grey, &format!("{:>3}", pos + 1)),
+                color(
+                    Color::SuperCyan,
+                    &cell(&self.quiz.questions[record.index].text, 40)
+                ),
+                status
+            );
+        }
+        println!();
+    }
+
+    pub fn settle(&mut self, pos: usize, answer: String, verdict: Verdict) {
+        let question = &self.quiz.questions[self.records[pos].index];
+        let record = &mut self.records[pos];
+
+        record.points = match verdict {
+            Verdict::Correct => run::award(question.value, record.attempts, self.quiz.decay),
+            _ => 0,
+        };
+        record.answer = answer;
+        record.verdict = verdict;
+    }
+
+    pub fn attempt(&mut self, pos: usize, answer: &str) -> Attempt {
+        if self.locked(pos) {
+            return Attempt::Locked;
+        }
+
+        let question = &self.quiz.questions[self.records[pos].index];
+        let tries = match self.exam {
+            true => 1,
+            false => question.attempts.or(self.quiz.attempts).unwrap_or(1),
+        };
+
+        // revising an answer in exam mode isn't another attempt
+        if !self.exam || self.records[pos].attempts == 0 {
+            self.records[pos].attempts += 1;
+        }
+        let attempts = self.records[pos].attempts;
+
+        if run::judge(question, answer) {
+            self.settle(pos, answer.to_string(), Verdict::Correct);
+        } else if attempts < tries {
+            return Attempt::Retry(tries - attempts);
+        } else {
+            self.settle(pos, answer.to_string(), Verdict::Wrong);
+        }
+
+        Attempt::Resolved
+    }
+
+    fn feedback(&mut self, pos: usize) {
+        let record = &self.records[pos];
+        let question = &self.quiz.questions[record.index];
+
+        match record.verdict {
+            _ if self.exam => println!(),
+            Verdict::Correct if record.points != question.value => println!(
+                "{} {}\n",
+                color(Color::Green, "correct answer!"),
+                color(
+                    Color::Grey,
+                    &format!("[{} after {} attempts]", record.points, record.attempts)
+                )
+            ),
+            Verdict::Correct => println!("{}\n", color(Color::Green, "correct answer!")),
+            Verdict::Wrong => println!(
+                "{} \n",
+                color(
+                    Color::Red,
+                    &format!("wrong answer!\n└─ expected {}", run::expected(question))
+                )
+            ),
+            Verdict::Timeout => println!(
+                "{} \n",
+                color(
+                    Color::Red,
+                    &format!("timed out!\n└─ expected {}", run::expected(question))
+                )
+            ),
+            Verdict::Skipped => unreachable!(),
+        }
+
+        if let Some(s) = question.section
+            && !self.exam
+            && self
+                .records
+                .iter()
+                .filter(|r| self.quiz.questions[r.index].section == Some(s))
+                .all(|r| r.verdict != Verdict::Skipped)
+        {
+            self.subtotal(s);
+        }
+    }
+
+    fn subtotal(&mut self, section: usize) {
+        subtotal(
+            &self.quiz.sections[section].name,
+            run::tally(self.quiz, &self.records, |q| q.section == Some(section)),
+            self.section_pass[section],
+        );
+        self.shown[section] = true;
+    }
+
+    fn ask(&mut self, lines: &Receiver<String>, pos: usize) -> State {
+        if self.expired() {
+            return self.expire();
+        }
+
+        if self.asked != Some(pos) {
+            self.show(pos);
+        }
+
+        let line = match self.read(lines, Some(pos)) {
+            Input::Line(line) => line,
+            Input::Closed => return State::Aborted,
+            Input::Timeout if self.expired() => return self.expire(),
+            Input::Timeout => {
+                println!();
+                while lines.try_recv().is_ok() {}
+                self.settle(pos, String::new(), Verdict::Timeout);
+                self.feedback(pos);
+                return self.next(pos);
+            }
+        };
+
+        let answer = line.trim();
+
+        match answer {
+            ":skip" => return self.next(pos),
+            ":submit" => return State::Submit,
+            ":quit" => return State::Aborted,
+            ":list" => {
+                self.list();
+                self.asked = None;
+                return State::Ask(pos);
+            }
+            ":back" if pos == 0 => {
+                println!("{}", color(Color::Red, "this is the first question"));
+                return State::Ask(pos);
+            }
+            ":back" => return State::Ask(pos - 1),
+            _ => {}
+        }
+
+        if let Some(n) = answer.strip_prefix(":goto") {
+            return match n.trim().parse::<usize>() {
+                Ok(n) if (1..=self.records.len()).contains(&n) => State::Ask(n - 1),
+                _ => {
+                    println!(
+                        "{}",
+                        color(
+                            Color::Red,
+                            &format!("expected a question between 1 and {}", self.records.len())
+                        )
+                    );
+                    State::Ask(pos)
+                }
+            };
+        }
+
+        match self.attempt(pos, answer) {
+            Attempt::Locked => {
+                println!("{}", color(Color::Red, "this question is already answered"));
+                return State::Ask(pos);
+            }
+            Attempt::Retry(left) => {
+                println!(
+                    "{}",
+                    color(
+                        Color::Red,
+                        &format!(
+                            "wrong answer! {} left",
+                            match left {
+                                1 => "1 attempt".to_string(),
+                                n => format!("{} attempts", n),
+                            }
+                        )
+                    )
+                );
+                return State::Ask(pos);
+            }
+            Attempt::Resolved => self.feedback(pos),
+        }
+
+        self.next(pos)
+    }
+
+    fn submit(&mut self, lines: &Receiver<String>) -> State {
+        let unanswered = self.unanswered();
+        if unanswered == 0 {
+            return State::Done;
+        }
+
+        println!(
+            "{}",
+            color(
+                Color::Yellow,
+                &format!(
+                    "you have {} unanswered question{}, submit? [y/n]",
+                    unanswered,
+                    if unanswered == 1 { "" } else { "s" }
+                )
+            )
+        );
+
+        match self.read(lines, None) {
+            Input::Line(line) => match line.trim() {
+                "y" | "yes" => {
+                    println!();
+                    State::Done
+                }
+                "n" | "no" => {
+                    println!();
+                    self.asked = None;
+                    State::Ask(
+                        self.records
+                            .iter()
+                            .position(|r| r.verdict == Verdict::Skipped)
+                            .unwrap_or_default(),
+                    )
+                }
+                _ => State::Submit,
+            },
+            Input::Timeout => self.expire(),
+            Input::Closed => State::Aborted,
+        }
+    }
+
+    pub fn save(&self, done: bool) {
+        let Some(path) = &self.opts.session else {
+            return;
+        };
+
+        let mut out = format!(
+            "qqg session\nfile {}\nhash {:016x}\nseed {}\nexam {}\nelapsed {}\ndone {}\n",
+            self.file,
+            self.hash,
+            self.seed,
+            self.exam,
+            self.elapsed.as_millis(),
+            done
+        );
+
+        if self.opts.no_history {
+            out += "history false\n";
+        }
+
+        for tag in &self.opts.tags {
+            out += &format!("tag {}\n", tag);
+        }
+
+        for tag in &self.opts.exclude {
+            out += &format!("exclude {}\n", tag);
+        }
+
+        if let Some((format, path)) = &self.opts.report {
+            out += &format!("report {} {}\n", format.name(), path);
+        }
+
+        if let Some(streak) = self.opts.practice {
+            out += &format!("practice {}\n", streak);
+        }
+
+        if self.opts.tui {
+            out += "tui true\n";
+        }
+
+        if self.opts.hide_score {
+            out += "hide-score true\n";
+        }
+
+        for record in &self.records {
+            out += &format!(
+                "record {} {} {} {} {} {}\n",
+                record.index,
+                record.verdict.name(),
+                record.points,
+                record.attempts,
+                record.time.as_millis(),
+                record.answer
+            );
+        }
+
+        std::fs::write(path, out).unwrap_or_else(|e| {
+            fatal!(
+                Exit::Io;
+                "{}",
+                color(Color::Red, &format!("failed to save session: {}", e))
+            )
+        });
+    }
+
+    pub fn run(&mut self, lines: &Receiver<String>) -> bool {
+        let mut state = self.next(usize::MAX);
+        if state == State::Submit && self.unanswered() == 0 {
+            state = State::Done;
+        }
+
+        loop {
+            state = match state {
+                State::Ask(pos) => self.ask(lines, pos),
+                State::Submit => self.submit(lines),
+                State::Done => return true,
+                State::Aborted => return false,
+            };
+            self.save(state == State::Done);
+        }
+    }
+
+    pub fn grade(&mut self, answers: Vec<Option<String>>) {
+        self.exam = true;
+
+        for (record, answer) in self.records.iter_mut().zip(answers) {
+            let Some(answer) = answer else {
+                continue;
+            };
+            let question = &self.quiz.questions[record.index];
+
+            record.attempts = 1;
+            if run::judge(question, &answer) {
+                record.verdict = Verdict::Correct;
+                record.points = question.value;
+            } else {
+                record.verdict = Verdict::Wrong;
+            }
+            record.answer = answer;
+        }
+    }
+
+    pub fn report(&mut self) -> Exit {
+        let summary = self.finish();
+        self.conclude(&summary)
+    }
+
+    fn conclude(&self, summary: &Summary) -> Exit {
+        if let Some((format, path)) = &self.opts.report {
+            report::write(self, summary, *format, path);
+        }
+
+        if summary.passed {
+            Exit::Passed
+        } else {
+            Exit::Failed
+        }
+    }
+
+    pub fn practice(&mut self, lines: &Receiver<String>, streak: usize) -> bool {
+        self.drills = self
+            .records
+            .iter()
+            .filter(|r| r.verdict != Verdict::Correct)
+            .map(|r| Drill {
+                index: r.index,
+                asked: 0,
+                correct: 0,
+                streak: 0,
+            })
+            .collect();
+
+        if self.drills.is_empty() {
+            println!("\n{}", color(Color::Green, "nothing to practice!"));
+            return true;
+        }
+
+        println!(
+            "\n{}\n",
+            color(
+                Color::Grey,
+                &format!(
+                    "practice: missed questions come back until answered correctly {} times in a row",
+                    streak
+                )
+            )
+        );
+
+        let mut queue = (0..self.drills.len()).collect::<VecDeque<_>>();
+        while let Some(d) = queue.pop_front() {
+            let question = &self.quiz.questions[self.drills[d].index];
+            run::show(question);
+
+            let answer = match run::prompt(lines, None) {
+                Input::Line(line) if line.trim() != ":quit" => line.trim().to_string(),
+                _ => {
+                    println!("\n{}\n", color(Color::Red, "practice stopped"));
+                    return false;
+                }
+            };
+
+            let drill = &mut self.drills[d];
+            drill.asked += 1;
+
+            if run::judge(question, &answer) {
+                drill.correct += 1;
+                drill.streak += 1;
+                println!(
+                    "{} {}\n",
+                    color(Color::Green, "correct answer!"),
+                    color(Color::Grey, &format!("[{}/{}]", drill.streak, streak))
+                );
+            } else {
+                drill.streak = 0;
+                println!(
+                    "{} \n",
+                    color(
+                        Color::Red,
+                        &format!("wrong answer!\n└─ expected {}", run::expected(question))
+                    )
+                );
+            }
+
+            if drill.streak < streak {
+                queue.push_back(d);
+            }
+        }
+
+        true
+    }
+
+    fn mastery(&self, streak: usize) {
+        if self.drills.is_empty() {
+            return;
+        }
+
+        println!("{}", color(Color::Yellow, "mastery"));
+        for drill in &self.drills {
+            println!(
+                "{} {} {}",
+                color(
+                    Color::SuperCyan,
+                    &cell(&self.quiz.questions[drill.index].text, 40)
+                ),
+                color(
+                    Color::Grey,
+                    &cell(&format!("{}/{} correct", drill.correct, drill.asked), 12)
+                ),
+                if drill.streak >= streak {
+                    color(Color::Green, "mastered")
+                } else {
+                    color(Color::Red, "not mastered")
+                }
+            );
+        }
+
+        let (correct, asked) = self
+            .drills
+            .iter()
+            .fold((0, 0), |(c, a), d| (c + d.correct, a + d.asked));
+        println!(
+            "{} {} {} {}",
+            color(Color::Yellow, "practice score"),
+            color(Color::SuperCyan, &correct.to_string()),
+            color(Color::Yellow, "out of"),
+            color(Color::SuperCyan, &asked.to_string())
+        );
+    }
+
+    pub fn complete(&mut self) -> Exit {
+        let finished = match self.opts.tui {
+            true => tui::run(self),
+            false => None,
+        };
+        let lines = &run::lines();
+        let finished = match finished {
+            Some(finished) => {
+                self.header();
+                println!();
+                finished
+            }
+            None => {
+                self.intro();
+                self.run(lines)
+            }
+        };
+
+        if !finished {
+            println!("\n{}", color(Color::Red, "quiz aborted"));
+
+            if let Some(path) = &self.opts.session {
+                println!(
+                    "{} {}",
+                    color(Color::Grey, "progress saved, continue with"),
+                    color(Color::Yellow, &format!("qqg resume {}", path))
+                );
+            }
+
+            return Exit::Aborted;
+        }
+
+        let summary = self.finish();
+        if let Some(streak) = self.opts.practice {
+            self.practice(lines, streak);
+            self.mastery(streak);
+        }
+
+        let exit = self.conclude(&summary);
+        if !self.opts.no_history {
+            history::append(self, &summary);
+        }
+
+        exit
+    }
+
+    pub fn summary(&self) -> Summary {
+        let quiz = self.quiz;
+        let (score, total) = run::tally(quiz, &self.records, |_| true);
+        let failed = (0..quiz.sections.len())
+            .filter(|&s| {
+                run::tally(quiz, &self.records, |q| q.section == Some(s)).0 < self.section_pass[s]
+            })
+            .collect::<Vec<_>>();
+
+        Summary {
+            score,
+            total,
+            pass: self.pass,
+            passed: score >= self.pass && (!quiz.strict || failed.is_empty()),
+            failed,
+        }
+    }
+
+    pub fn finish(&mut self) -> Summary {
+        if self.exam {
+            review(self.quiz, &self.records);
+        }
+
+        for s in 0..self.quiz.sections.len() {
+            if !self.shown[s]
+                && self
+                    .records
+                    .iter()
+                    .any(|r| self.quiz.questions[r.index].section == Some(s))
+            {
+                self.subtotal(s);
+            }
+        }
+
+        let quiz = self.quiz;
+        let records = &self.records;
+        let summary = self.summary();
+        let (score, total, pass) = (summary.score, summary.total, summary.passed);
+        let failed = summary
+            .failed
+            .iter()
+            .map(|&s| quiz.sections[s].name.as_str())
+            .collect::<Vec<_>>();
+
+        println!(
+            "{} {} {} {}",
+            color(Color::Yellow, "you scored"),
+            color(
+                if pass { Color::Green } else { Color::Red },
+                &score.to_string()
+            ),
+            color(Color::Yellow, "out of"),
+            color(Color::SuperCyan, &total.to_string())
+        );
+
+        let mut tags = Vec::<&str>::new();
+        for record in records {
+            for tag in &quiz.questions[record.index].tags {
+                if !tags.contains(&tag.as_str()) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        if !tags.is_empty() {
+            println!("{}", color(Color::Grey, "by tag:"));
+            for tag in tags {
+                let (score, total) = run::tally(quiz, records, |q| q.tags.iter().any(|t| t == tag));
+                println!(
+                    "{} {} {} {}",
+                    color(Color::Yellow, &format!("  {}", tag)),
+                    color(Color::SuperCyan, &score.to_string()),
+                    color(Color::Grey, "out of"),
+                    color(Color::SuperCyan, &total.to_string())
+                );
+            }
+        }
+
+        if !failed.is_empty() {
+            println!(
+                "{} {}",
+                color(Color::Grey, "sections not passed:"),
+                color(Color::Red, &failed.join(", "))
+            );
+        }
+
+        println!(
+            "{}",
+            if pass {
+                color(Color::Green, "you passed!")
+            } else {
+                color(Color::Red, "you failed!")
+            }
+        );
+
+        summary
+    }
+}
+
+fn corrupt(path: &str, line: &str) -> ! {
+    fatal!(
+        Exit::Quiz;
+        "{}",
+        color(
+            Color::Red,
+            &format!("encountered malformed line in session {}: {}", path, line)
+        )
+    )
+}
+
+pub fn resume(path: &str, text: &str) -> Exit {
+    let mut lines = text.lines();
+    if lines.next() != Some("qqg session") {
+        fatal!(
+            Exit::Quiz;
+            "{}",
+            color(Color::Red, &format!("{} is not a qqg session", path))
+        );
+    }
+
+    let mut file = String::new();
+    let mut saved = 0;
+    let mut opts = Options {
+        session: Some(path.to_string()),
+        ..Default::default()
+    };
+    let mut elapsed = Duration::ZERO;
+    let mut done = false;
+    let mut records = Vec::new();
+
+    for line in lines {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+
+        match key {
+            "file" => file = value.to_string(),
+            "hash" => {
+                saved = u64::from_str_radix(value, 16).unwrap_or_else(|_| corrupt(path, line))
+            }
+            "seed" => opts.seed = Some(value.parse().unwrap_or_else(|_| corrupt(path, line))),
+            "exam" => opts.exam = value == "true",
+            "elapsed" => {
+                elapsed =
+                    Duration::from_millis(value.parse().unwrap_or_else(|_| corrupt(path, line)))
+            }
+            "done" => done = value == "true",
+            "practice" => {
+                opts.practice = Some(value.parse().unwrap_or_else(|_| corrupt(path, line)))
+            }
+            "tui" => opts.tui = value == "true",
+            "hide-score" => opts.hide_score = value == "true",
+            "history" => opts.no_history = value == "false",
+            "tag" => opts.tags.push(value.to_string()),
+            "exclude" => opts.exclude.push(value.to_string()),
+            "report" => {
+                let (format, output) = value.split_once(' ').unwrap_or_else(|| corrupt(path, line));
+                opts.report = Some((
+                    Format::from_name(format).unwrap_or_else(|| corrupt(path, line)),
+                    output.to_string(),
+                ));
+            }
+            "record" => {
+                let mut fields = value.splitn(6, ' ');
+                let mut field = || fields.next().unwrap_or_else(|| corrupt(path, line));
+
+                records.push(Record {
+                    index: field().parse().unwrap_or_else(|_| corrupt(path, line)),
+                    verdict: Verdict::from_name(field()).unwrap_or_else(|| corrupt(path, line)),
+                    points: field().parse().unwrap_or_else(|_| corrupt(path, line)),
+                    attempts: field().parse().unwrap_or_else(|_| corrupt(path, line)),
+                    time: Duration::from_millis(
+                        field().parse().unwrap_or_else(|_| corrupt(path, line)),
+                    ),
+                    answer: field().to_string(),
+                });
+            }
+            _ => corrupt(path, line),
+        }
+    }
+
+    if done {
+        fatal!(Exit::Quiz; "{}", color(Color::Red, "this session is already finished"));
+    }
+
+    let source = std::fs::read_to_string(&file).unwrap_or_else(|e| {
+        fatal!(
+            Exit::Io;
+            "{}",
+            color(
+                Color::Red,
+                &format!("failed to open quiz {}: {}", file, e)
+            )
+        )
+    });
+
+    if hash(&source) != saved {
+        fatal!(
+            Exit::Quiz;
+            "{}",
+            color(
+                Color::Red,
+                &format!(
+                    "refusing to resume: {} has changed since the session was saved",
+                    file
+                )
+            )
+        );
+    }
+
+    let quiz = ast::ify(&mut token::ize(&file, &source), &file);
+    let mut session = Session::new(&quiz, &opts);
+    session.source(&file, &source);
+
+    if session.records.len() != records.len()
+        || session
+            .records
+            .iter()
+            .zip(&records)
+            .any(|(a, b)| a.index != b.index)
+    {
+        fatal!(
+            Exit::Quiz;
+            "{}",
+            color(
+                Color::Red,
+                "refusing to resume: the saved questions don't match the quiz"
+            )
+        );
+    }
+
+    session.records = records;
+    session.elapsed = elapsed;
+
+    session.complete()
+}
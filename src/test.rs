@@ -1,4 +1,7 @@
-use crate::{args, ast, token::*, utils};
+use crate::{
+    args, ast, cards, export, history, host, http, import, json, party, report, review, run,
+    session, token::*, tui, utils, xml, zip,
+};
 use std::io::IsTerminal;
 use utils::Location;
 
@@ -15,9 +18,24 @@ fn args_parse() {
         ),
         args::Info {
             cmd: args::Command::Start,
-            file: "input.qq".to_string()
+            file: "input.qq".to_string(),
+            ..Default::default()
         }
     );
+
+    assert!(args::accepts(&args::Command::Start, "--tui"));
+    assert!(args::accepts(&args::Command::Serve, "--port"));
+    assert!(!args::accepts(&args::Command::Start, "--port"));
+    assert!(!args::accepts(&args::Command::Grade, "--exam"));
+    assert!(!args::accepts(&args::Command::Resume, "--seed"));
+    assert!(args::accepts(
+        &args::Command::Export(export::Target::Markdown),
+        "--answer-key"
+    ));
+    assert!(!args::accepts(
+        &args::Command::Export(export::Target::Gift),
+        "--answer-key"
+    ));
 }
 
 #[test]
@@ -31,6 +49,46 @@ fn utils_color() {
     }
 }
 
+#[test]
+fn utils_duration() {
+    assert_eq!(utils::duration(30), "30s");
+    assert_eq!(utils::duration(600), "10m");
+    assert_eq!(utils::duration(90), "1m30s");
+    assert_eq!(utils::duration(3660), "1h1m");
+    assert_eq!(utils::duration(3661), "1h1m1s");
+}
+
+#[test]
+fn utils_hash() {
+    assert_eq!(utils::hash(""), 0xcbf29ce484222325);
+    assert_eq!(utils::hash("a"), 0xaf63dc4c8601ec8c);
+    assert_ne!(utils::hash("pass 10"), utils::hash("pass 11"));
+}
+
+#[test]
+fn utils_date() {
+    assert_eq!(utils::date(0), "1970-01-01 00:00");
+    assert_eq!(utils::date(951782400), "2000-02-29 00:00");
+    assert_eq!(utils::date(1792390853), "2026-10-19 06:20");
+}
+
+#[test]
+fn token_ize_duration() {
+    assert_eq!(
+        ize("test.qq", "time 30s 10m 2h 5")
+            .into_iter()
+            .map(|t| t.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            TokenKind::Time,
+            TokenKind::Duration(30),
+            TokenKind::Duration(600),
+            TokenKind::Duration(7200),
+            TokenKind::Number(5),
+        ]
+    );
+}
+
 #[test]
 fn token_ize() {
     assert_eq!(
@@ -123,13 +181,1174 @@ fn ast_ify() {
                     options: vec![2]
                 },
                 text: "does life have any meaning?".to_string(),
-                value: 3
+                value: 3,
+                ..Default::default()
             }],
             metaline: ast::Metaline {
                 title: "test quiz".to_string(),
                 by: "sarkar-segfault".to_string(),
                 pass: 3
-            }
+            },
+            ..Default::default()
         }
     );
 }
+
+#[test]
+fn run_draw() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 10
+            draw 2
+            question "a" { answer { "a" } value 5 }
+            question "b" { answer { "b" } value 5 }
+            question "c" { answer { "c" } value 5 }
+            question "d" { answer { "d" } value 5 }"#,
+        ),
+        "test.qq",
+    );
+
+    let opts = run::Options::default();
+    let drawn = run::draw(&quiz, &opts, &mut utils::Rng::new(42));
+    assert_eq!(drawn.len(), 2);
+    assert!(drawn.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(drawn, run::draw(&quiz, &opts, &mut utils::Rng::new(42)));
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 4
+            draw 2 from "history"
+            draw 1 from "geography"
+            question "a" { answer { "a" } value 1 tags { "history" } }
+            question "b" { answer { "b" } value 1 tags { "history", "geography" } }
+            question "c" { answer { "c" } value 1 tags { "history" } }
+            question "d" { answer { "d" } value 1 tags { "geography" } }
+            question "e" { answer { "e" } value 1 tags { "geography" } }
+            question "f" { answer { "f" } value 1 }"#,
+        ),
+        "test.qq",
+    );
+    assert_eq!(
+        quiz.pools,
+        vec![
+            ast::Pool {
+                tag: "history".to_string(),
+                draw: 2
+            },
+            ast::Pool {
+                tag: "geography".to_string(),
+                draw: 1
+            }
+        ]
+    );
+    assert_eq!(run::pool(&quiz, &quiz.questions[1]), Some(0));
+    assert_eq!(run::pool(&quiz, &quiz.questions[5]), None);
+    for seed in 0..20 {
+        let drawn = run::draw(&quiz, &opts, &mut utils::Rng::new(seed));
+        assert_eq!(drawn.len(), 4);
+        assert!(drawn.contains(&5));
+        assert_eq!(drawn.iter().filter(|&&i| i <= 2).count(), 2);
+        assert_eq!(drawn.iter().filter(|&&i| i == 3 || i == 4).count(), 1);
+    }
+
+    assert_eq!(run::required(10, 10, 20), 5);
+    assert_eq!(run::required(15, 10, 20), 8);
+    assert_eq!(run::required(10, 20, 20), 10);
+}
+
+#[test]
+fn ast_ify_section() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 3
+            strict
+            question "a" { answer { "a" } value 1 }
+            section "history" pass 2 {
+                draw 1
+                question "b" { answer { "b" } value 2 }
+                question "c" { answer { "c" } value 2 }
+            }"#,
+        ),
+        "test.qq",
+    );
+
+    assert!(quiz.strict);
+    assert_eq!(
+        quiz.sections,
+        vec![ast::Section {
+            name: "history".to_string(),
+            pass: 2,
+            draw: Some(1)
+        }]
+    );
+    assert_eq!(
+        quiz.questions.iter().map(|q| q.section).collect::<Vec<_>>(),
+        vec![None, Some(0), Some(0)]
+    );
+
+    let drawn = run::draw(&quiz, &run::Options::default(), &mut utils::Rng::new(7));
+    assert_eq!(drawn.len(), 2);
+    assert_eq!(drawn[0], 0);
+    assert_eq!(
+        run::required(2, run::marks(&quiz, drawn.into_iter(), Some(0)), 4),
+        1
+    );
+}
+
+#[test]
+fn run_filter() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"question "a" { answer { "a" } tags { "ownership", "macros" } }
+            question "b" { answer { "b" } tags { "macros" } }
+            question "c" { answer { "c" } }"#,
+        ),
+        "test.qq",
+    );
+
+    assert_eq!(quiz.questions[0].tags, vec!["ownership", "macros"]);
+
+    let args = |args: &[&str]| {
+        args::parse(
+            ["qqg", "start", "input.qq"]
+                .iter()
+                .chain(args)
+                .map(|s| s.to_string()),
+        )
+        .opts
+    };
+    let mut rng = utils::Rng::new(0);
+
+    assert_eq!(
+        run::draw(&quiz, &args(&["--tag", "macros"]), &mut rng),
+        [0, 1]
+    );
+    assert_eq!(
+        run::draw(&quiz, &args(&["--exclude-tag", "ownership"]), &mut rng),
+        [1, 2]
+    );
+    assert_eq!(
+        run::draw(
+            &quiz,
+            &args(&["--tag", "macros", "--exclude-tag", "ownership"]),
+            &mut rng
+        ),
+        [1]
+    );
+}
+
+#[test]
+fn run_award() {
+    assert_eq!(run::award(8, 1, None), 8);
+    assert_eq!(run::award(8, 2, None), 4);
+    assert_eq!(run::award(8, 3, None), 2);
+    assert_eq!(run::award(8, 3, Some(100)), 8);
+    assert_eq!(run::award(8, 2, Some(0)), 0);
+    assert_eq!(run::award(1, 2, None), 1);
+    assert_eq!(run::award(5, 3, None), 2);
+    assert_eq!(run::award(3, 2, Some(25)), 1);
+
+    let question = ast::Question {
+        answer: ast::Answer {
+            answers: vec!["a".to_string(), "b".to_string()],
+            options: vec![2],
+        },
+        ..Default::default()
+    };
+    assert!(run::judge(&question, "2"));
+    assert!(!run::judge(&question, "1"));
+    assert!(!run::judge(&question, "b"));
+    assert!(!run::judge(&question, "1,2"));
+
+    let question = ast::Question {
+        answer: ast::Answer {
+            answers: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            options: vec![1, 3],
+        },
+        ..Default::default()
+    };
+    assert!(run::judge(&question, "3"));
+    assert!(run::judge(&question, "3,1"));
+    assert!(run::judge(&question, "1 3"));
+    assert!(!run::judge(&question, "1,2,3"));
+    assert!(!run::judge(&question, "1,x"));
+}
+
+#[test]
+fn ast_ify_mode() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            "title \"exam\" by \"sarkar-segfault\" pass 1 mode exam",
+        ),
+        "test.qq",
+    );
+    assert_eq!(quiz.mode, ast::Mode::Exam);
+    assert_eq!(ast::Quiz::default().mode, ast::Mode::Quiz);
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            "title \"quiz\" by \"sarkar-segfault\" pass 1 mode quiz",
+        ),
+        "test.qq",
+    );
+    assert_eq!(quiz.mode, ast::Mode::Quiz);
+}
+
+#[test]
+fn session_run() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 2
+            question "a" { answer { "a" } value 1 }
+            question "b" { answer { "b" } value 1 attempts 2 }
+            question "c" { answer { "c" } value 1 }"#,
+        ),
+        "test.qq",
+    );
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    for line in [":skip", "x", "b", ":goto 1", "a", ":list", ":back", "n"] {
+        tx.send(line.to_string()).unwrap();
+    }
+    drop(tx);
+
+    let mut session = session::Session::new(&quiz, &run::Options::default());
+    session.run(&rx);
+
+    assert_eq!(
+        session
+            .records
+            .iter()
+            .map(|r| (r.verdict, r.points, r.attempts))
+            .collect::<Vec<_>>(),
+        vec![
+            (run::Verdict::Correct, 1, 1),
+            (run::Verdict::Correct, 1, 2),
+            (run::Verdict::Skipped, 0, 0),
+        ]
+    );
+    assert_eq!(
+        session.progress(2),
+        "question 3/3 · score 2 · 1 still possible"
+    );
+    session.opts.hide_score = true;
+    assert_eq!(session.progress(2), "question 3/3");
+
+    let summary = session.finish();
+    assert!(summary.passed);
+    assert_eq!((summary.score, summary.total, summary.pass), (2, 3, 2));
+
+    let csv = report::csv(&session, &summary);
+    assert_eq!(csv.lines().count(), 4);
+    assert!(csv.lines().nth(3).unwrap().contains(",c,skipped,0,1,0,"));
+    assert!(report::json(&session, &summary).contains(r#""verdict":"skipped""#));
+
+    session.student = Some("alice".to_string());
+    assert!(report::csv(&session, &summary).starts_with("student,title,"));
+    assert!(report::json(&session, &summary).starts_with(r#"{"student":"alice","#));
+}
+
+#[test]
+fn session_exam() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 20 mode exam
+            question "a" { answer { "a" } value 10 }
+            question "b" { answer { "b" } value 10 }"#,
+        ),
+        "test.qq",
+    );
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    for line in ["x", ":back", "a", "b"] {
+        tx.send(line.to_string()).unwrap();
+    }
+    drop(tx);
+
+    let mut session = session::Session::new(&quiz, &run::Options::default());
+    session.run(&rx);
+
+    let summary = session.finish();
+    assert_eq!(
+        session
+            .records
+            .iter()
+            .map(|r| (r.verdict, r.points, r.attempts))
+            .collect::<Vec<_>>(),
+        vec![
+            (run::Verdict::Correct, 10, 1),
+            (run::Verdict::Correct, 10, 1),
+        ]
+    );
+    assert!(summary.passed);
+    assert_eq!((summary.score, summary.total), (20, 20));
+}
+
+#[test]
+fn session_practice() {
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 2
+            question "a" { answer { "a" } value 1 }
+            question "b" { answer { "b" } value 1 }
+            question "c" { answer { "c" } value 1 }"#,
+        ),
+        "test.qq",
+    );
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    for line in ["a", "x", "x", "b", "x", "b", "c", "c"] {
+        tx.send(line.to_string()).unwrap();
+    }
+    drop(tx);
+
+    let mut session = session::Session::new(&quiz, &run::Options::default());
+    session.run(&rx);
+    assert!(session.practice(&rx, 2));
+
+    assert_eq!(
+        session
+            .drills
+            .iter()
+            .map(|d| (d.index, d.asked, d.correct))
+            .collect::<Vec<_>>(),
+        vec![(1, 2, 2), (2, 3, 2)]
+    );
+    assert_eq!(session.summary().score, 1);
+}
+
+#[test]
+fn history_entry() {
+    let entry = history::Entry {
+        time: 1792390853,
+        hash: utils::hash("quiz"),
+        score: 7,
+        total: 10,
+        passed: true,
+        questions: vec![
+            (utils::hash("first"), run::Verdict::Correct),
+            (utils::hash("second"), run::Verdict::Timeout),
+        ],
+        file: "/tmp/my\tquiz.qq".to_string(),
+    };
+
+    assert_eq!(history::Entry::parse(&entry.line()), Some(entry));
+    assert_eq!(history::Entry::parse("1792390853\tnot hex"), None);
+}
+
+#[test]
+fn review_schedule() {
+    let mut card = review::Card {
+        quiz: 3,
+        id: 1,
+        text: 2,
+        ease: 250,
+        interval: 0,
+        reps: 0,
+        due: 100,
+    };
+
+    card.schedule(4, 100);
+    assert_eq!(
+        (card.interval, card.reps, card.ease, card.due),
+        (1, 1, 250, 101)
+    );
+    card.schedule(5, 101);
+    assert_eq!(
+        (card.interval, card.reps, card.ease, card.due),
+        (6, 2, 260, 107)
+    );
+    card.schedule(3, 107);
+    assert_eq!(
+        (card.interval, card.reps, card.ease, card.due),
+        (16, 3, 246, 123)
+    );
+    card.schedule(1, 123);
+    assert_eq!(
+        (card.interval, card.reps, card.ease, card.due),
+        (1, 0, 192, 124)
+    );
+
+    for _ in 0..5 {
+        card.schedule(0, 124);
+    }
+    assert_eq!(card.ease, 130);
+    assert_eq!(review::Card::parse(&card.line()), Some(card.clone()));
+    assert_eq!(
+        review::Card::parse("0000000000000001\t0000000000000002\t130\t1\t0\t125"),
+        Some(review::Card { quiz: 0, ..card })
+    );
+
+    let parse = |text: &str| ast::ify(&mut ize("test.qq", text), "test.qq");
+    let first = parse(
+        r#"title "a" by "sarkar-segfault" pass 1
+        question "same" { answer { "x" } value 1 }
+        question "same" { answer { "y" } value 1 }"#,
+    );
+    let second = parse(
+        r#"title "b" by "sarkar-segfault" pass 1
+        question "same" { answer { "z" } value 1 }"#,
+    );
+    let opts = run::Options::default();
+    let mut cards = Vec::new();
+
+    let (deck, edited) = review::deck(&first, 1, &opts, &mut cards, 100);
+    assert_eq!((deck, edited), (vec![(0, 0), (1, 1)], 0));
+    cards[0].schedule(5, 100);
+
+    let (deck, edited) = review::deck(&second, 2, &opts, &mut cards, 100);
+    assert_eq!((deck, edited), (vec![(0, 2)], 0));
+    assert_eq!(cards[0].reps, 1);
+
+    let edit = parse(
+        r#"title "a" by "sarkar-segfault" pass 1
+        question "same" { answer { "x" } value 1 }
+        question "same" { answer { "w" } value 1 }"#,
+    );
+    let (deck, edited) = review::deck(&edit, 1, &opts, &mut cards, 100);
+    assert_eq!((deck, edited), (vec![(0, 0), (1, 1)], 1));
+    assert_eq!((cards.len(), cards[0].reps), (3, 1));
+}
+
+#[test]
+fn cards_rating() {
+    assert_eq!(
+        cards::Rating::from_name("again"),
+        Some(cards::Rating::Again)
+    );
+    assert_eq!(cards::Rating::from_name("3"), Some(cards::Rating::Good));
+    assert_eq!(cards::Rating::from_name("5"), None);
+    assert_eq!(cards::Rating::ALL.map(|r| r.quality()), [1, 3, 4, 5]);
+}
+
+#[test]
+fn tui_key() {
+    assert_eq!(tui::key(b"\x1b[A"), Some(tui::Key::Up));
+    assert_eq!(tui::key(b"\x1bOD"), Some(tui::Key::Left));
+    assert_eq!(tui::key(b"\x1b"), Some(tui::Key::Quit));
+    assert_eq!(tui::key(b"\r"), Some(tui::Key::Enter));
+    assert_eq!(tui::key("é".as_bytes()), Some(tui::Key::Char('é')));
+    assert_eq!(tui::key(b"\x1b[5~"), None);
+    assert_eq!(tui::key(b""), None);
+    assert_eq!(tui::bar(1, 4, 8), "██░░░░░░");
+    assert_eq!(tui::bar(0, 0, 2), "██");
+}
+
+#[test]
+fn party_ranking() {
+    let player = |name: &str, score, correct, secs| party::Player {
+        name: name.to_string(),
+        score,
+        correct,
+        time: std::time::Duration::from_secs(secs),
+    };
+    let players = [
+        player("alice", 5, 2, 30),
+        player("bob", 7, 2, 50),
+        player("carol", 5, 3, 20),
+        player("dave", 5, 2, 30),
+    ];
+
+    assert_eq!(
+        party::ranking(&players, party::TieBreak::Time),
+        vec![(1, 1), (2, 2), (3, 0), (3, 3)]
+    );
+    assert_eq!(
+        party::ranking(&players, party::TieBreak::Correct),
+        vec![(1, 1), (2, 2), (3, 0), (3, 3)]
+    );
+    assert_eq!(
+        party::ranking(&players, party::TieBreak::None),
+        vec![(1, 1), (2, 0), (2, 2), (2, 3)]
+    );
+}
+
+#[test]
+fn host_serve() {
+    use std::io::{BufRead, BufReader, Write};
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 2
+            question "a" { answer { "a" } value 2 }
+            question "b" { answer { "x", "y" pass } value 3 }"#,
+        ),
+        "test.qq",
+    );
+    let setup = party::Options {
+        expect: Some(2),
+        deadline: Some(5),
+        ..Default::default()
+    };
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let server = std::thread::spawn(move || {
+        host::serve(&quiz, &run::Options::default(), &setup, listener, (tx, rx))
+    });
+
+    let client = |name: &'static str, answers: [&'static str; 2]| {
+        std::thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(address).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut transcript = String::new();
+            writeln!(stream, "{}", name).unwrap();
+
+            let mut asked = 0;
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                if line.starts_with("question ") {
+                    writeln!(stream, "{}", answers[asked]).unwrap();
+                    asked += 1;
+                }
+                transcript += &line;
+                line.clear();
+            }
+            transcript
+        })
+    };
+
+    let alice = client("alice", ["a", "2"]);
+    let bob = client("bob", ["b", "2"]);
+    let alice = alice.join().unwrap();
+    bob.join().unwrap();
+    let (finished, mut players) = server.join().unwrap();
+    players.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert!(finished);
+    assert_eq!(
+        players
+            .iter()
+            .map(|p| (p.name.as_str(), p.score, p.correct))
+            .collect::<Vec<_>>(),
+        vec![("alice", 5, 2), ("bob", 3, 1)]
+    );
+    assert!(alice.contains("correct! +2"));
+    assert!(alice.contains("final leaderboard"));
+}
+
+#[test]
+fn host_accept() {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let failed = std::iter::once(Err(std::io::Error::other("refused")));
+        host::accept(failed.chain(listener.incoming()), tx)
+    });
+
+    let _first = std::net::TcpStream::connect(address).unwrap();
+    assert!(matches!(rx.recv().unwrap(), host::Event::Connect(0, _)));
+    let _second = std::net::TcpStream::connect(address).unwrap();
+    assert!(matches!(rx.recv().unwrap(), host::Event::Connect(1, _)));
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 1
+            question "a" { answer { "a" } value 1 }"#,
+        ),
+        "test.qq",
+    );
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let host = tx.clone();
+    let server = std::thread::spawn(move || {
+        host::serve(
+            &quiz,
+            &run::Options::default(),
+            &party::Options::default(),
+            listener,
+            (tx, rx),
+        )
+    });
+
+    let mut client = std::net::TcpStream::connect(address).unwrap();
+    client.write_all(b"alice\n").unwrap();
+    let mut transcript = String::new();
+    while !transcript.contains("you're in!") {
+        let mut buf = [0; 256];
+        let n = client.read(&mut buf).unwrap();
+        transcript += &String::from_utf8_lossy(&buf[..n]);
+    }
+    host.send(host::Event::Host(":quit".to_string())).unwrap();
+
+    let (finished, players) = server.join().unwrap();
+    client.read_to_string(&mut transcript).unwrap();
+    assert!(!finished);
+    assert_eq!(players.len(), 1);
+    assert!(!transcript.contains("starting"));
+}
+
+#[test]
+fn http_respond() {
+    assert_eq!(
+        http::form("seed=7&q0=a%20b+c&q1=1&q1=3&bad=%zz&%E2%9C%93"),
+        vec![
+            ("seed".to_string(), "7".to_string()),
+            ("q0".to_string(), "a b c".to_string()),
+            ("q1".to_string(), "1".to_string()),
+            ("q1".to_string(), "3".to_string()),
+            ("bad".to_string(), "%zz".to_string()),
+            ("✓".to_string(), String::new()),
+        ]
+    );
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test <quiz>" by "sarkar-segfault" pass 3
+            question "a" { answer { "secret" } value 2 }
+            question "b" { answer { "x", "y" pass, "z" pass } value 2 }
+            question "c" { answer { "c" } value 0 }"#,
+        ),
+        "test.qq",
+    );
+    assert_eq!(
+        http::read("POST /submit?x=1 HTTP/1.1\r\nContent-Length: 3\r\n\r\nq=a".as_bytes()),
+        Ok(http::Request {
+            method: "POST".to_string(),
+            path: "/submit".to_string(),
+            body: "q=a".to_string(),
+        })
+    );
+    let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(http::LINE_LIMIT));
+    assert_eq!(http::read(long.as_bytes()), Err("400 Bad Request"));
+    let long = format!(
+        "GET / HTTP/1.1\r\nX: {}\r\n\r\n",
+        "a".repeat(http::LINE_LIMIT)
+    );
+    assert_eq!(
+        http::read(long.as_bytes()),
+        Err("431 Request Header Fields Too Large")
+    );
+    let many = format!(
+        "GET / HTTP/1.1\r\n{}\r\n",
+        "X: a\r\n".repeat(http::HEADER_LIMIT + 1)
+    );
+    assert_eq!(
+        http::read(many.as_bytes()),
+        Err("431 Request Header Fields Too Large")
+    );
+
+    let opts = run::Options::default();
+    let server = http::Server {
+        quiz: &quiz,
+        opts: &opts,
+        pending: std::sync::Mutex::new(std::collections::HashMap::new()),
+        submissions: std::sync::Mutex::new(0),
+    };
+    let request = |method: &str, path: &str, body: &str| http::Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        body: body.to_string(),
+    };
+
+    let page = server.respond(&request("GET", "/", "")).body;
+    assert!(page.contains("<title>test &lt;quiz&gt;</title>"));
+    assert!(page.contains(r#"name="q1" value="3""#));
+    assert!(page.contains(r#"name="name" required"#));
+    assert!(!page.contains("secret"));
+    assert!(!page.contains("seed"));
+
+    let token = page
+        .split(r#"name="token" value=""#)
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap();
+    let body = format!("token={}&name=alice&q0=wrong&q1=2&q1=3&q2=c", token);
+
+    let result = server.respond(&request("POST", "/submit", &body));
+    assert_eq!(result.status, "200 OK");
+    assert!(result.body.contains("you scored 2 out of 4, you failed!"));
+    assert!(result.body.contains(r#"<tr class="correct"><td>3</td>"#));
+    assert!(!result.body.contains("secret"));
+    assert_eq!(
+        server.respond(&request("POST", "/submit", &body)).status,
+        "409 Conflict"
+    );
+    assert_eq!(
+        server
+            .respond(&request("POST", "/submit", "token=x&q0=secret"))
+            .status,
+        "400 Bad Request"
+    );
+    assert_eq!(
+        server
+            .respond(&request("POST", "/submit", "seed=1&name=bob&q0=secret"))
+            .status,
+        "400 Bad Request"
+    );
+    assert_eq!(
+        server
+            .respond(&request("GET", "/results/1.json", ""))
+            .status,
+        "404 Not Found"
+    );
+
+    for n in 0..http::PENDING_LIMIT {
+        server
+            .pending
+            .lock()
+            .unwrap()
+            .insert(n.to_string(), (0, std::time::Instant::now()));
+    }
+    server.respond(&request("GET", "/", ""));
+    assert_eq!(server.pending.lock().unwrap().len(), http::PENDING_LIMIT);
+}
+
+#[test]
+fn json_display() {
+    use json::Json;
+
+    assert_eq!(
+        Json::Object(vec![
+            ("text".to_string(), "say \"hi\"\n</script>".into()),
+            ("points".to_string(), (-3isize).into()),
+            (
+                "tags".to_string(),
+                Json::Array(vec![true.into(), "a".into()])
+            ),
+        ])
+        .to_string(),
+        r#"{"text":"say \"hi\"\n\u003c/script>","points":-3,"tags":[true,"a"]}"#
+    );
+}
+
+#[test]
+fn run_grade() {
+    assert_eq!(
+        run::answers("a\n\n  c  \nd", 3),
+        vec![Some("a".to_string()), None, Some("c".to_string())]
+    );
+    assert_eq!(
+        run::answers("3: c\n1:a\n", 3),
+        vec![Some("a".to_string()), None, Some("c".to_string())]
+    );
+    assert_eq!(
+        run::answers("2: b\nnote: c", 2),
+        vec![Some("2: b".to_string()), Some("note: c".to_string())]
+    );
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 4
+            question "a" { answer { "a" } value 2 }
+            question "b" { answer { "x", "y" pass } value 2 }
+            question "c" { answer { "c" } value 2 }"#,
+        ),
+        "test.qq",
+    );
+
+    assert!(!run::draws(&quiz));
+    assert!(run::draws(&ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test quiz" by "sarkar-segfault" pass 1
+            section "s" pass 1 { draw 1 question "a" { answer { "a" } value 1 } }"#,
+        ),
+        "test.qq",
+    )));
+
+    let mut session = session::Session::new(&quiz, &run::Options::default());
+    session.grade(run::answers("1: a\n2: 2", 3));
+
+    assert_eq!(
+        session
+            .records
+            .iter()
+            .map(|r| r.verdict)
+            .collect::<Vec<_>>(),
+        vec![
+            run::Verdict::Correct,
+            run::Verdict::Correct,
+            run::Verdict::Skipped
+        ]
+    );
+    assert_eq!(session.report(), utils::Exit::Passed);
+}
+
+#[test]
+fn export_html() {
+    assert_eq!(export::html::digest("", "a"), "e40c292c");
+    assert_eq!(export::html::digest("", ""), "811c9dc5");
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test </script>" by "sarkar-segfault" pass 3
+            question "a" { answer { "secret" } value 2 }
+            question "b" { answer { "x", "y" pass, "z" pass } value 2 }"#,
+        ),
+        "test.qq",
+    );
+
+    let plain = export::html::html(&quiz, &run::Options::default(), &export::Options::default());
+    assert!(plain.contains("<title>test &lt;/script&gt;</title>"));
+    assert!(plain.contains(r#""title":"test \u003c/script>""#));
+    assert!(plain.contains(r#""answers":["secret"]"#));
+    assert!(plain.contains(r#""answers":["2","3"],"set":"2,3""#));
+    assert_eq!(plain.matches("</script>").count(), 2);
+
+    let opts = run::Options {
+        seed: Some(1),
+        ..Default::default()
+    };
+    let setup = export::Options {
+        obfuscate: true,
+        ..Default::default()
+    };
+    let hidden =
+        String::from_utf8(export::export(&quiz, &opts, &setup, export::Target::Html)).unwrap();
+    let salt = format!("{:016x}", utils::Rng::new(1).next());
+    assert!(!hidden.contains("secret"));
+    assert!(!hidden.contains(r#""expected":"#));
+    assert!(hidden.contains(&format!(r#""salt":"{}""#, salt)));
+    assert!(hidden.contains(&format!(
+        r#""answers":["{}"]"#,
+        export::html::digest(&salt, "secret")
+    )));
+    assert!(hidden.contains(&format!(
+        r#""set":"{}""#,
+        export::html::digest(&salt, "2,3")
+    )));
+}
+
+#[test]
+fn export_markdown() {
+    assert_eq!(
+        export::markdown::escape("a_b *c* | <d>"),
+        r"a\_b \*c\* \| \<d\>"
+    );
+    assert_eq!(
+        [0, 1, 25, 26, 27, 701, 702].map(export::markdown::letter),
+        ["a", "b", "z", "aa", "ab", "zz", "aaa"]
+    );
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test" by "sarkar-segfault" pass 3
+            section "one" pass 1 {
+                question "a?" { answer { "secret", "other" } value 1 }
+            }
+            question "b?" { answer { "x", "y" pass, "z" pass } value 2 }"#,
+        ),
+        "test.qq",
+    );
+    let opts = run::Options::default();
+    let mut setup = export::Options::default();
+
+    assert_eq!(
+        String::from_utf8(export::export(
+            &quiz,
+            &opts,
+            &setup,
+            export::Target::Markdown
+        ))
+        .unwrap(),
+        "# test\n\nby sarkar-segfault\n\npassing marks 3 of 3 · 2 questions\n\n\
+        ## one\n\npassing marks 1\n\n\
+        **1.** a? *(1 mark)*\n\nanswer: ______________________________\n\n\
+        **2.** b? *(2 marks)*\n\n- a) x\n- b) y\n- c) z\n\nanswer: ______________________________\n\n"
+    );
+
+    setup.answer_key = true;
+    assert_eq!(
+        String::from_utf8(export::export(
+            &quiz,
+            &opts,
+            &setup,
+            export::Target::Markdown
+        ))
+        .unwrap(),
+        "# test\n\nby sarkar-segfault\n\npassing marks 3 of 3 · 2 questions\n\n\
+        ## answer key\n\n| # | answer | marks |\n|---|--------|-------|\n\
+        | 1 | secret or other | 1 |\n| 2 | b) y, c) z | 2 |\n"
+    );
+}
+
+#[test]
+fn export_latex() {
+    assert_eq!(
+        export::latex::escape(r"50% of $5 & #1_a {b} ~c^ \d [e]"),
+        r"50\% of \$5 \& \#1\_a \{b\} \textasciitilde{}c\textasciicircum{} \textbackslash{}d {[}e{]}"
+    );
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test & co" by "sarkar-segfault" pass 3
+            question "a?" { answer { "secret", "other" } value 1 }
+            question "b?" { answer { "x", "y" pass } value 2 }"#,
+        ),
+        "test.qq",
+    );
+    let opts = run::Options::default();
+    let mut setup = export::Options::default();
+
+    let paper =
+        String::from_utf8(export::export(&quiz, &opts, &setup, export::Target::Latex)).unwrap();
+    assert!(paper.starts_with("\\documentclass[addpoints]{exam}\n"));
+    assert!(paper.contains("\\title{test \\& co}\n"));
+    assert!(paper.contains(
+        "\\question[1] a?\n\\begin{solutionorlines}[1in]\nsecret or other\n\\end{solutionorlines}\n"
+    ));
+    assert!(paper.contains(
+        "\\question[2] b?\n\\begin{choices}\n\\choice x\n\\CorrectChoice y\n\\end{choices}\n"
+    ));
+    assert!(paper.ends_with("\\end{questions}\n\\end{document}\n"));
+    assert!(!paper.contains("\\printanswers"));
+
+    setup.solutions = true;
+    assert!(
+        String::from_utf8(export::export(&quiz, &opts, &setup, export::Target::Latex))
+            .unwrap()
+            .contains("\\printanswers\n")
+    );
+}
+
+#[test]
+fn gift_round_trip() {
+    for entry in std::fs::read_dir("samples").unwrap() {
+        let path = entry.unwrap().path().to_string_lossy().into_owned();
+        let quiz = ast::ify(
+            &mut ize(&path, &std::fs::read_to_string(&path).unwrap()),
+            &path,
+        );
+
+        let gift = export::gift::gift(&quiz, &run::Options::default());
+        let (imported, diagnostics) = import::parse("quiz.gift", &gift, import::Source::Gift);
+        assert_eq!(imported, quiz, "{}", path);
+        assert_eq!(diagnostics, vec![], "{}", path);
+
+        let text = export::qq::qq(&imported);
+        assert_eq!(ast::ify(&mut ize(&path, &text), &path), quiz, "{}", path);
+    }
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test" by "sarkar-segfault" pass 3 draw 2 draw 1 from "t" strict time 10m attempts 2 decay 25 mode exam
+            section "one" pass 1 {
+                draw 1
+                question "a {b} = c?" { answer { "x:y", "~z#" } value 1 tags { "t", "u" } time 90s attempts 3 }
+            }
+            section "empty" pass 0 { }
+            question "b?" { answer { "x", "y" pass, "z" pass } value 2 }
+            question "c?" { answer { "x", "y" pass } value 2 }"#,
+        ),
+        "test.qq",
+    );
+    let gift = export::gift::gift(&quiz, &run::Options::default());
+    assert!(gift.contains("a \\{b\\} \\= c? {=x\\:y =\\~z\\#}\n"));
+    assert!(gift.contains("{~%-100%x ~%50%y ~%50%z}\n"));
+    assert!(gift.contains("{~x =y}\n"));
+    assert!(gift.contains("// qqg draw 1 from \"t\"\n"));
+
+    let (imported, diagnostics) = import::parse("quiz.gift", &gift, import::Source::Gift);
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(imported.questions, quiz.questions);
+    assert_eq!(imported.pools, quiz.pools);
+    assert_eq!(imported.sections.len(), 1);
+
+    let text = export::qq::qq(&quiz);
+    assert_eq!(ast::ify(&mut ize("test.qq", &text), "test.qq"), quiz);
+
+    let mut quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test" by "sarkar-segfault" pass 1
+            draw 1 from "t"
+            question "a" { answer { "a" } value 1 tags { "t" } }"#,
+        ),
+        "test.qq",
+    );
+    quiz.metaline.title = r#"the "best" quiz \ ever"#.to_string();
+    quiz.metaline.by = r#"sarkar "segfault""#.to_string();
+    quiz.questions[0].tags = vec![r#"t"u"#.to_string()];
+    quiz.pools[0].tag = r#"t"u"#.to_string();
+
+    let gift = export::gift::gift(&quiz, &run::Options::default());
+    assert!(gift.starts_with(r#"// qqg title "the \"best\" quiz \\ ever" by "#));
+
+    let (imported, diagnostics) = import::parse("quiz.gift", &gift, import::Source::Gift);
+    assert_eq!(diagnostics.len(), 4);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.message.contains("double quotes"))
+    );
+    assert_eq!(imported.metaline.title, r#"the 'best' quiz \ ever"#);
+    assert_eq!(imported.metaline.by, "sarkar 'segfault'");
+    assert_eq!(imported.questions[0].tags, vec!["t'u".to_string()]);
+    assert_eq!(imported.pools[0].tag, "t'u");
+
+    let text = export::qq::qq(&imported);
+    assert_eq!(ast::ify(&mut ize("test.qq", &text), "test.qq"), imported);
+}
+
+#[test]
+fn gift_import() {
+    let (quiz, diagnostics) = import::parse(
+        "bank.gift",
+        "// a comment\n::Q1:: Who's buried in Grant's tomb?{~Grant =no one#right}\n\n\
+        Grant is {~buried =entombed} in Grant's tomb.\n\n\
+        The sky is green.{F}\n\n\
+        $CATEGORY: maths\n\
+        What is 2 + 2?{#4:0.5}\n\n\
+        Match the capitals. {\n=Canada -> Ottawa\n=Italy -> Rome\n}\n\n\
+        Pick a number.{#1..5}\n\n\
+        Write an \"essay\".{}\n",
+        import::Source::Gift,
+    );
+
+    assert_eq!(quiz.metaline.title, "bank");
+    assert_eq!(quiz.metaline.pass, 3);
+    assert_eq!(quiz.sections.len(), 1);
+    assert_eq!(
+        quiz.questions
+            .iter()
+            .map(|q| (
+                q.text.as_str(),
+                q.answer.answers.join("|"),
+                q.answer.options.clone(),
+                q.section
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "Who's buried in Grant's tomb?",
+                "Grant|no one".to_string(),
+                vec![2],
+                None
+            ),
+            (
+                "Grant is _____ in Grant's tomb.",
+                "buried|entombed".to_string(),
+                vec![2],
+                None
+            ),
+            ("The sky is green.", "true|false".to_string(), vec![2], None),
+            ("What is 2 + 2?", "4".to_string(), vec![], Some(0)),
+            (
+                "Match the capitals. Canada",
+                "Ottawa".to_string(),
+                vec![],
+                Some(0)
+            ),
+            (
+                "Match the capitals. Italy",
+                "Rome".to_string(),
+                vec![],
+                Some(0)
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostics.iter().map(|d| d.line).collect::<Vec<_>>(),
+        vec![1, 2, 2, 9, 11, 16, 18, 18]
+    );
+}
+
+#[test]
+fn export_xml() {
+    assert_eq!(
+        xml::escape("a < b & \"c\"\u{1}", false),
+        "a &lt; b &amp; \"c\""
+    );
+    assert_eq!(xml::escape("'c'\n", true), "&apos;c&apos;&#10;");
+    assert_eq!(
+        xml::Xml::new("a")
+            .attr("k", "<v>")
+            .child(xml::Xml::new("b").text("x & y"))
+            .child(xml::Xml::new("c"))
+            .to_string(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<a k=\"&lt;v&gt;\">\n  <b>x &amp; y</b>\n  <c/>\n</a>\n"
+    );
+    assert_eq!(zip::crc32(b"123456789"), 0xcbf43926);
+
+    let quiz = ast::ify(
+        &mut ize(
+            "test.qq",
+            r#"title "test" by "sarkar-segfault" pass 3
+            question "a < b?" { answer { "x & y" } value 1 tags { "t" } }
+            question "b?" { answer { "x", "y" pass, "z" pass } value 2 }"#,
+        ),
+        "test.qq",
+    );
+    let opts = run::Options::default();
+
+    let moodle = export::moodle::moodle(&quiz, &opts);
+    assert!(moodle.contains("<text>$course$/top/test</text>"));
+    assert!(moodle.contains("<questiontext format=\"plain_text\">\n      <text>a &lt; b?</text>"));
+    assert!(
+        moodle.contains(
+            "<answer fraction=\"100\" format=\"plain_text\">\n      <text>x &amp; y</text>"
+        )
+    );
+    assert!(moodle.contains("<single>false</single>"));
+    assert!(
+        moodle.contains("<answer fraction=\"-100\" format=\"plain_text\">\n      <text>x</text>")
+    );
+    assert!(
+        moodle.contains("<answer fraction=\"50\" format=\"plain_text\">\n      <text>y</text>")
+    );
+
+    let mut many = quiz.questions[0].clone();
+    many.answer = ast::Answer {
+        answers: (1..=12).map(|n| n.to_string()).collect(),
+        options: (1..=11).collect(),
+    };
+    let many = export::moodle::moodle(
+        &ast::Quiz {
+            questions: vec![many],
+            ..Default::default()
+        },
+        &opts,
+    );
+    assert!(many.contains("<single>true</single>"));
+    assert_eq!(many.matches("<answer fraction=\"100\"").count(), 11);
+    assert!(many.contains("<answer fraction=\"0\" format=\"plain_text\">\n      <text>12</text>"));
+
+    let package = export::qti::qti(&quiz, &opts);
+    let end = package.len() - 22;
+    assert_eq!(&package[..4], b"PK\x03\x04");
+    assert_eq!(&package[end..end + 4], b"PK\x05\x06");
+    assert_eq!(
+        u16::from_le_bytes([package[end + 10], package[end + 11]]),
+        4
+    );
+
+    let text = String::from_utf8_lossy(&package);
+    for name in [
+        "imsmanifest.xml",
+        "test.xml",
+        "items/item-1.xml",
+        "items/item-2.xml",
+    ] {
+        assert!(text.contains(&format!("{}<?xml", name)));
+    }
+    assert!(
+        text.contains(
+            "<textEntryInteraction responseIdentifier=\"RESPONSE\" expectedLength=\"30\"/>"
+        )
+    );
+    assert!(text.contains(
+        "<choiceInteraction responseIdentifier=\"RESPONSE\" shuffle=\"false\" maxChoices=\"0\">"
+    ));
+}
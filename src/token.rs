@@ -4,6 +4,7 @@ use crate::{token_error, utils::Location};
 pub enum TokenKind {
     String(String),
     Number(isize),
+    Duration(u64),
     LBrace,
     RBrace,
     Comma,
@@ -14,6 +15,17 @@ pub enum TokenKind {
     Title,
     Pass,
     By,
+    Draw,
+    From,
+    Section,
+    Strict,
+    Tags,
+    Time,
+    Attempts,
+    Decay,
+    Mode,
+    Quiz,
+    Exam,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -106,15 +118,48 @@ pub fn ize(file: &str, text: &str) -> TokenStream {
                     buf.push(dig);
                 }
 
+                let number = buf.parse::<isize>().unwrap_or_else(|e| {
+                    token_error!(
+                        begin,
+                        loc,
+                        &format!("failed to parse number {}: {}", buf, e),
+                        file
+                    )
+                });
+
+                let kind = match chars.peek() {
+                    Some(&unit) if unit.is_alphabetic() => {
+                        chars.next();
+                        loc.col += 1;
+
+                        let scale = match unit {
+                            's' => 1,
+                            'm' => 60,
+                            'h' => 3600,
+                            _ => token_error!(
+                                begin,
+                                loc,
+                                &format!("encountered unrecognized duration unit: {}", unit),
+                                file
+                            ),
+                        };
+
+                        if number < 0 {
+                            token_error!(
+                                begin,
+                                loc,
+                                &format!("encountered negative duration: {}{}", buf, unit),
+                                file
+                            );
+                        }
+
+                        TokenKind::Duration((number as u64).saturating_mul(scale))
+                    }
+                    _ => TokenKind::Number(number),
+                };
+
                 let out = Token {
-                    kind: TokenKind::Number(buf.parse::<isize>().unwrap_or_else(|e| {
-                        token_error!(
-                            begin,
-                            loc,
-                            &format!("failed to parse number {}: {}", buf, e),
-                            file
-                        )
-                    })),
+                    kind,
                     begin,
                     end: loc,
                 };
@@ -142,6 +187,17 @@ pub fn ize(file: &str, text: &str) -> TokenStream {
                         "title" => TokenKind::Title,
                         "pass" => TokenKind::Pass,
                         "by" => TokenKind::By,
+                        "draw" => TokenKind::Draw,
+                        "from" => TokenKind::From,
+                        "section" => TokenKind::Section,
+                        "strict" => TokenKind::Strict,
+                        "tags" => TokenKind::Tags,
+                        "time" => TokenKind::Time,
+                        "attempts" => TokenKind::Attempts,
+                        "decay" => TokenKind::Decay,
+                        "mode" => TokenKind::Mode,
+                        "quiz" => TokenKind::Quiz,
+                        "exam" => TokenKind::Exam,
                         _ => token_error!(
                             begin,
                             loc,
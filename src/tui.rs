@@ -0,0 +1,588 @@
+use crate::{
+    run::{self, Verdict},
+    session::{Attempt, Session, State},
+    utils::{Color, color, duration},
+};
+use std::io::{IsTerminal, Read, Write};
+use std::time::{Duration, Instant};
+
+// the termios layout and ioctl numbers differ between architectures, these are
+// the ones of x86_64 and aarch64 linux
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod sys {
+    use std::ffi::{c_int, c_ulong};
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Termios {
+        pub iflag: u32,
+        pub oflag: u32,
+        pub cflag: u32,
+        pub lflag: u32,
+        pub line: u8,
+        pub cc: [u8; 32],
+        pub ispeed: u32,
+        pub ospeed: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    pub struct Winsize {
+        pub rows: u16,
+        pub cols: u16,
+        pub xpixel: u16,
+        pub ypixel: u16,
+    }
+
+    pub const ISIG: u32 = 0o1;
+    pub const ICANON: u32 = 0o2;
+    pub const ECHO: u32 = 0o10;
+    pub const IEXTEN: u32 = 0o100000;
+    pub const ICRNL: u32 = 0o400;
+    pub const IXON: u32 = 0o2000;
+    pub const VTIME: usize = 5;
+    pub const VMIN: usize = 6;
+    pub const TCSANOW: c_int = 0;
+    pub const TIOCGWINSZ: c_ulong = 0x5413;
+
+    unsafe extern "C" {
+        pub fn tcgetattr(fd: c_int, termios: *mut Termios) -> c_int;
+        pub fn tcsetattr(fd: c_int, action: c_int, termios: *const Termios) -> c_int;
+        pub fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+}
+
+struct Raw {
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    original: sys::Termios,
+}
+
+impl Raw {
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    fn enter() -> Option<Self> {
+        let mut termios = sys::Termios::default();
+        if unsafe { sys::tcgetattr(0, &mut termios) } != 0 {
+            return None;
+        }
+
+        let original = termios;
+        termios.lflag &= !(sys::ICANON | sys::ECHO | sys::ISIG | sys::IEXTEN);
+        termios.iflag &= !(sys::IXON | sys::ICRNL);
+        termios.cc[sys::VMIN] = 0;
+        termios.cc[sys::VTIME] = 1;
+
+        if unsafe { sys::tcsetattr(0, sys::TCSANOW, &termios) } != 0 {
+            return None;
+        }
+
+        print!("\x1b[?1049h\x1b[?25l");
+        Some(Self { original })
+    }
+
+    #[cfg(not(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    fn enter() -> Option<Self> {
+        None
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    fn size(&self) -> (usize, usize) {
+        let mut size = sys::Winsize::default();
+        match unsafe { sys::ioctl(1, sys::TIOCGWINSZ, &mut size as *mut sys::Winsize) } {
+            0 if size.cols > 0 && size.rows > 0 => (size.cols as usize, size.rows as usize),
+            _ => (80, 24),
+        }
+    }
+
+    #[cfg(not(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    fn size(&self) -> (usize, usize) {
+        (80, 24)
+    }
+}
+
+impl Drop for Raw {
+    fn drop(&mut self) {
+        #[cfg(all(
+            target_os = "linux",
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        ))]
+        unsafe {
+            sys::tcsetattr(0, sys::TCSANOW, &self.original);
+        }
+
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Space,
+    Tab,
+    Backspace,
+    Quit,
+    Char(char),
+}
+
+pub fn key(bytes: &[u8]) -> Option<Key> {
+    match bytes {
+        [0x1b, b'[' | b'O', b'A'] => Some(Key::Up),
+        [0x1b, b'[' | b'O', b'B'] => Some(Key::Down),
+        [0x1b, b'[' | b'O', b'C'] => Some(Key::Right),
+        [0x1b, b'[' | b'O', b'D'] => Some(Key::Left),
+        [0x1b] | [0x03] | [0x04] => Some(Key::Quit),
+        [b'\r'] | [b'\n'] => Some(Key::Enter),
+        [b' '] => Some(Key::Space),
+        [b'\t'] => Some(Key::Tab),
+        [0x7f] | [0x08] => Some(Key::Backspace),
+        [0x1b, ..] => None,
+        _ => match std::str::from_utf8(bytes).ok()?.chars().collect::<Vec<_>>()[..] {
+            [c] if !c.is_control() => Some(Key::Char(c)),
+            _ => None,
+        },
+    }
+}
+
+fn fit(text: &str, width: usize) -> String {
+    let mut out = text.chars().take(width).collect::<String>();
+    if text.chars().count() > width {
+        out.pop();
+        out.push('…');
+    }
+    out
+}
+
+pub fn bar(done: usize, total: usize, width: usize) -> String {
+    let filled = (done * width).checked_div(total).unwrap_or(width);
+    "█".repeat(filled) + &"░".repeat(width - filled)
+}
+
+struct Tui<'s, 'q> {
+    session: &'s mut Session<'q>,
+    state: State,
+    cursor: usize,
+    selected: Vec<bool>,
+    buffer: String,
+    message: Option<(Color, String)>,
+}
+
+impl Tui<'_, '_> {
+    fn goto(&mut self, state: State) {
+        if let State::Ask(pos) = state {
+            let quiz = self.session.quiz;
+            let question = &quiz.questions[self.session.records[pos].index];
+            self.cursor = 0;
+            self.selected = vec![false; question.answer.answers.len()];
+            self.buffer.clear();
+
+            if self.state != state {
+                self.session.save(false);
+            }
+        }
+
+        self.message = None;
+        self.state = state;
+    }
+
+    fn clock(&self) -> String {
+        let pos = match self.state {
+            State::Ask(pos) => Some(pos),
+            _ => None,
+        };
+
+        match self.session.deadline(pos) {
+            Some(deadline) => format!(
+                "{} left",
+                duration(
+                    deadline
+                        .saturating_duration_since(Instant::now())
+                        .as_secs_f64()
+                        .ceil() as u64
+                )
+            ),
+            None => format!("{} elapsed", duration(self.session.elapsed.as_secs())),
+        }
+    }
+
+    fn draw(&self, (cols, rows): (usize, usize)) {
+        let session = &*self.session;
+        let quiz = session.quiz;
+        let answered = session
+            .records
+            .iter()
+            .filter(|r| r.verdict != Verdict::Skipped)
+            .count();
+        let total = session.records.len();
+        let mut lines = vec![
+            format!(
+                "{} {} {}",
+                color(Color::Yellow, &fit(&quiz.metaline.title, cols / 2)),
+                color(Color::Grey, "by"),
+                color(Color::Yellow, &fit(&quiz.metaline.by, cols / 4))
+            ),
+            format!(
+                "{} {} {}",
+                color(
+                    Color::SuperCyan,
+                    &bar(answered, total, cols.saturating_sub(40).clamp(5, 30))
+                ),
+                color(Color::Grey, &format!("{}/{} answered", answered, total)),
+                color(Color::Yellow, &self.clock())
+            ),
+            String::new(),
+        ];
+
+        match self.state {
+            State::Ask(pos) => {
+                let record = &session.records[pos];
+                let question = &quiz.questions[record.index];
+
+                if let Some(s) = question.section {
+                    lines.push(color(
+                        Color::Yellow,
+                        &fit(&format!("§ {}", quiz.sections[s].name), cols),
+                    ));
+                }
+
+                let label = format!("{} [{}]", session.progress(pos), question.value);
+                lines.push(color(Color::Grey, &fit(&label, cols)));
+                lines.push(color(
+                    Color::SuperCyan,
+                    &fit(&format!("┌ {}", question.text), cols),
+                ));
+
+                if question.answer.options.is_empty() {
+                    lines.push(format!(
+                        "{} {}",
+                        color(Color::SuperCyan, "└──"),
+                        fit(&(self.buffer.clone() + "_"), cols.saturating_sub(4))
+                    ));
+                } else {
+                    for (index, answer) in question.answer.answers.iter().enumerate() {
+                        let text = format!(
+                            "{} {} {}",
+                            if self.selected[index] { "[x]" } else { "[ ]" },
+                            index + 1,
+                            answer
+                        );
+
+                        lines.push(if index == self.cursor {
+                            format!(
+                                "{} {}",
+                                color(Color::SuperCyan, "›"),
+                                color(Color::Yellow, &fit(&text, cols.saturating_sub(2)))
+                            )
+                        } else {
+                            format!("  {}", fit(&text, cols.saturating_sub(2)))
+                        });
+                    }
+                }
+
+                match record.verdict {
+                    Verdict::Skipped => {}
+                    Verdict::Timeout => lines.push(color(Color::Grey, "└─ timed out")),
+                    _ => lines.push(color(
+                        Color::Grey,
+                        &fit(
+                            &format!(
+                                "└─ {} \"{}\"",
+                                if session.exam {
+                                    "your answer"
+                                } else {
+                                    "already answered"
+                                },
+                                record.answer
+                            ),
+                            cols,
+                        ),
+                    )),
+                }
+            }
+            State::Submit => {
+                lines.push(color(
+                    Color::Yellow,
+                    &fit(
+                        &format!(
+                            "{} of {} questions are unanswered, submit anyway?",
+                            session.unanswered(),
+                            total
+                        ),
+                        cols,
+                    ),
+                ));
+            }
+            State::Done | State::Aborted => {}
+        }
+
+        lines.push(String::new());
+        if let Some((kind, message)) = &self.message {
+            for line in message.lines() {
+                lines.push(color(*kind, &fit(line, cols)));
+            }
+        }
+
+        let help = match self.state {
+            State::Submit => "enter submit  ← go back  esc quit",
+            _ => "↑↓ move  space select  enter answer  ←→ previous/next  tab submit  esc quit",
+        };
+        while lines.len() + 1 < rows {
+            lines.push(String::new());
+        }
+        lines.truncate(rows.saturating_sub(1));
+        lines.push(color(Color::Grey, &fit(help, cols)));
+
+        let mut out = String::from("\x1b[H");
+        for (n, line) in lines.iter().enumerate() {
+            out += line;
+            out += "\x1b[K";
+            if n + 1 < lines.len() {
+                out += "\r\n";
+            }
+        }
+
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
+    }
+
+    fn answer(&mut self, pos: usize) {
+        let quiz = self.session.quiz;
+        let question = &quiz.questions[self.session.records[pos].index];
+        let answer = if question.answer.options.is_empty() {
+            self.buffer.trim().to_string()
+        } else if self.selected.contains(&true) {
+            (0..self.selected.len())
+                .filter(|&i| self.selected[i])
+                .map(|i| (i + 1).to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        } else {
+            (self.cursor + 1).to_string()
+        };
+
+        if answer.is_empty() {
+            return;
+        }
+
+        match self.session.attempt(pos, &answer) {
+            Attempt::Locked => {
+                self.message = Some((Color::Red, "this question is already answered".into()))
+            }
+            Attempt::Retry(left) => {
+                self.message = Some((
+                    Color::Red,
+                    format!(
+                        "wrong answer! {} left",
+                        match left {
+                            1 => "1 attempt".to_string(),
+                            n => format!("{} attempts", n),
+                        }
+                    ),
+                ));
+                self.selected.fill(false);
+                self.buffer.clear();
+            }
+            Attempt::Resolved => {
+                let next = self.session.next(pos);
+                self.goto(next);
+
+                let record = &self.session.records[pos];
+                self.message = Some(match record.verdict {
+                    _ if self.session.exam => (Color::Grey, "answer saved".into()),
+                    Verdict::Correct if record.points != question.value => (
+                        Color::Green,
+                        format!(
+                            "correct answer! [{} after {} attempts]",
+                            record.points, record.attempts
+                        ),
+                    ),
+                    Verdict::Correct => (Color::Green, "correct answer!".into()),
+                    _ => (
+                        Color::Red,
+                        format!("wrong answer!\n└─ expected {}", run::expected(question)),
+                    ),
+                });
+            }
+        }
+    }
+
+    fn press(&mut self, key: Key) {
+        let State::Ask(pos) = self.state else {
+            match key {
+                Key::Enter => self.state = State::Done,
+                Key::Left | Key::Backspace => {
+                    let last = self.session.records.len() - 1;
+                    let pending = self.session.next(usize::MAX);
+                    self.goto(match pending {
+                        State::Ask(_) => pending,
+                        _ => State::Ask(last),
+                    });
+                }
+                Key::Quit => self.state = State::Aborted,
+                _ => {}
+            }
+            return;
+        };
+
+        let options = self.selected.len();
+        let text = self.session.quiz.questions[self.session.records[pos].index]
+            .answer
+            .options
+            .is_empty();
+
+        match key {
+            Key::Quit => self.state = State::Aborted,
+            Key::Tab => self.goto(State::Submit),
+            Key::Left if pos == 0 => {
+                self.message = Some((Color::Red, "this is the first question".into()))
+            }
+            Key::Left => self.goto(State::Ask(pos - 1)),
+            Key::Right if pos + 1 == self.session.records.len() => self.goto(State::Submit),
+            Key::Right => self.goto(State::Ask(pos + 1)),
+            Key::Up if !text => self.cursor = self.cursor.checked_sub(1).unwrap_or(options - 1),
+            Key::Down if !text => self.cursor = (self.cursor + 1) % options,
+            Key::Space if !text => self.selected[self.cursor] ^= true,
+            Key::Char(c) if !text => {
+                if let Some(n) = c.to_digit(10)
+                    && (1..=options).contains(&(n as usize))
+                {
+                    self.cursor = n as usize - 1;
+                }
+            }
+            Key::Space => self.buffer.push(' '),
+            Key::Char(c) => self.buffer.push(c),
+            Key::Backspace => {
+                self.buffer.pop();
+            }
+            Key::Enter => self.answer(pos),
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self, spent: Duration) {
+        self.session.elapsed += spent;
+
+        let State::Ask(pos) = self.state else {
+            return;
+        };
+
+        self.session.records[pos].time += spent;
+        let quiz = self.session.quiz;
+        let record = &self.session.records[pos];
+        let question = &quiz.questions[record.index];
+
+        if record.verdict == Verdict::Skipped
+            && question
+                .time
+                .is_some_and(|t| record.time >= Duration::from_secs(t))
+        {
+            self.message = Some((
+                Color::Red,
+                format!("timed out!\n└─ expected {}", run::expected(question)),
+            ));
+            self.session.settle(pos, String::new(), Verdict::Timeout);
+            let next = self.session.next(pos);
+            self.goto(next);
+        }
+    }
+}
+
+pub fn supported() -> bool {
+    cfg!(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )) && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+}
+
+pub fn run(session: &mut Session) -> Option<bool> {
+    if !supported() {
+        return None;
+    }
+    let raw = Raw::enter()?;
+
+    let mut tui = Tui {
+        session,
+        state: State::Submit,
+        cursor: 0,
+        selected: Vec::new(),
+        buffer: String::new(),
+        message: None,
+    };
+    let first = tui.session.next(usize::MAX);
+    tui.goto(first);
+
+    let mut stdin = std::io::stdin().lock();
+    let mut buf = [0; 16];
+    let mut last = Instant::now();
+    let mut drawn = None;
+
+    loop {
+        if tui.state == State::Submit && tui.session.unanswered() == 0 {
+            tui.state = State::Done;
+        }
+
+        match tui.state {
+            State::Done => {
+                tui.session.save(true);
+                return Some(true);
+            }
+            State::Aborted => {
+                tui.session.save(false);
+                return Some(false);
+            }
+            _ => {}
+        }
+
+        let frame = (raw.size(), tui.clock(), tui.state);
+        if drawn.as_ref() != Some(&frame) {
+            tui.draw(frame.0);
+            drawn = Some(frame);
+        }
+
+        let read = match stdin.read(&mut buf) {
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => 0,
+            Err(_) => {
+                tui.state = State::Aborted;
+                continue;
+            }
+        };
+        let now = Instant::now();
+        tui.tick(now - last);
+        last = now;
+
+        if tui.session.expired() {
+            tui.session.lapse();
+            tui.state = State::Done;
+            continue;
+        }
+
+        if let Some(key) = key(&buf[..read]) {
+            tui.press(key);
+            drawn = None;
+        }
+    }
+}
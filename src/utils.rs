@@ -1,5 +1,6 @@
 use std::io::IsTerminal;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Color {
     Yellow,
     Grey,
@@ -26,12 +27,111 @@ pub fn color(kind: Color, text: &str) -> String {
     )
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Passed = 0,
+    Failed = 1,
+    Usage = 2,
+    Quiz = 3,
+    Io = 4,
+    Aborted = 5,
+}
+
 #[macro_export]
 macro_rules! fatal {
-    ($($arg:tt)+) => {{
+    ($exit:expr; $($arg:tt)+) => {{
         eprintln!($($arg)+);
-        std::process::exit(1);
-    }}
+        std::process::exit($exit as i32);
+    }};
+    ($($arg:tt)+) => {
+        $crate::fatal!($crate::utils::Exit::Usage; $($arg)+)
+    };
+}
+
+pub fn duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    match (h, m, s) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m{}s", m, s),
+        (h, 0, 0) => format!("{}h", h),
+        (h, m, 0) => format!("{}h{}m", h, m),
+        (h, m, s) => format!("{}h{}m{}s", h, m, s),
+    }
+}
+
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn date(secs: u64) -> String {
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60
+    )
+}
+
+pub fn data_dir() -> Option<std::path::PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+    let base = var("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| var("APPDATA").map(std::path::PathBuf::from))
+        .or_else(|| {
+            var("HOME").map(|home| std::path::PathBuf::from(home).join(".local").join("share"))
+        })?;
+
+    Some(base.join("qqg"))
+}
+
+pub fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn seed() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -68,6 +168,7 @@ pub fn error(begin: Location, end: Location, msg: &str, file: &str, parse: bool)
     if begin.line == end.line {
         if begin.col == end.col {
             fatal!(
+                Exit::Quiz;
                 "{}{}{}{}{}{}{} {} {}",
                 lbrack,
                 file,
@@ -81,6 +182,7 @@ pub fn error(begin: Location, end: Location, msg: &str, file: &str, parse: bool)
             );
         }
         fatal!(
+            Exit::Quiz;
             "{}{}{}{} {}{}{}{} {} {}",
             lbrack,
             file,
@@ -95,6 +197,7 @@ pub fn error(begin: Location, end: Location, msg: &str, file: &str, parse: bool)
         );
     }
     fatal!(
+        Exit::Quiz;
         "{}{} {}{}{}{}{}{}{}{} {} {}",
         lbrack,
         file,
@@ -0,0 +1,90 @@
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Xml {
+    Element(String, Vec<(String, String)>, Vec<Xml>),
+    Text(String),
+}
+
+impl Xml {
+    pub fn new(name: &str) -> Self {
+        Xml::Element(name.to_string(), Vec::new(), Vec::new())
+    }
+
+    pub fn attr(mut self, key: &str, value: impl ToString) -> Self {
+        if let Xml::Element(_, attrs, _) = &mut self {
+            attrs.push((key.to_string(), value.to_string()));
+        }
+        self
+    }
+
+    pub fn child(mut self, child: Xml) -> Self {
+        if let Xml::Element(_, _, children) = &mut self {
+            children.push(child);
+        }
+        self
+    }
+
+    pub fn children(self, children: impl IntoIterator<Item = Xml>) -> Self {
+        children.into_iter().fold(self, Xml::child)
+    }
+
+    pub fn text(self, text: impl ToString) -> Self {
+        self.child(Xml::Text(text.to_string()))
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, depth: usize) -> Result {
+        let indent = "  ".repeat(depth);
+
+        match self {
+            Xml::Text(text) => write!(f, "{}{}", indent, escape(text, false)),
+            Xml::Element(name, attrs, children) => {
+                write!(f, "{}<{}", indent, name)?;
+                for (key, value) in attrs {
+                    write!(f, " {}=\"{}\"", key, escape(value, true))?;
+                }
+
+                match children.as_slice() {
+                    [] => write!(f, "/>"),
+                    [Xml::Text(text)] => write!(f, ">{}</{}>", escape(text, false), name),
+                    _ => {
+                        writeln!(f, ">")?;
+                        for child in children {
+                            child.write(f, depth + 1)?;
+                            writeln!(f)?;
+                        }
+                        write!(f, "{}</{}>", indent, name)
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn escape(text: &str, attr: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for chr in text.chars() {
+        match chr {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attr => out.push_str("&quot;"),
+            '\'' if attr => out.push_str("&apos;"),
+            '\n' | '\r' | '\t' if attr => out.push_str(&format!("&#{};", chr as u32)),
+            '\n' | '\r' | '\t' => out.push(chr),
+            c if (c as u32) < 0x20 || matches!(c, '\u{fffe}' | '\u{ffff}') => {}
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+impl Display for Xml {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        self.write(f, 0)?;
+        writeln!(f)
+    }
+}